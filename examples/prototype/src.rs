@@ -1,11 +1,54 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
 use rpg_system_2d::{
     area::{Area, AreaIdentifier, AreaPlugin, GameAreas, Passage, PassageDestination},
+    autosave::AutosavePlugin,
+    battle::{BattleEnemy, BattlePlugin, Encounter, EnemyGroup},
+    clock::ClockPlugin,
+    combat::{CombatPlugin, ContactDamage, Faction, Health},
+    consumable::ConsumablePlugin,
+    crafting::{Craft, CraftingPlugin, CraftingStation, RecipeBook, RecipeId},
+    dialogue::{Dialogue, DialogueLibrary, DialoguePlugin, DialogueTree},
+    encounter::{EncounterPlugin, EncounterRegion, EncounterTable, EncounterZone},
     enemy::Enemy,
+    equipment::{EquipmentPlugin, EquipmentSlot, Equippable, EquippedSprite},
+    flags::FlagsPlugin,
+    hud::HudPlugin,
+    interaction::{Interactable, Interacted, InteractionPlugin},
+    inventory::InventoryPlugin,
+    item::{ItemCategory, ItemDatabase, ItemDefinition, UseEffect},
+    loot::{LootDrop, LootEntry, LootPlugin, LootTable},
+    map::MapPlugin,
+    menu::MenuPlugin,
+    npc::{Npc, NpcPlugin, NpcSchedule, ScheduleEntry},
     physics::PhysicsPlugin,
-    player::PlayerPlugin,
+    pickup::{Pickup, PickupId, PickupPlugin, PlacedPickup},
+    player::{Player, PlayerPlugin},
+    progression::{ExperienceReward, ProgressionPlugin},
+    projectile::ProjectilePlugin,
+    quest::{QuestDatabase, QuestPlugin},
+    save::{PersistentId, SavePlugin},
+    shop::{Merchant, ShopPlugin, StockEntry},
+    state::GameStatePlugin,
+    stats::{Attributes, ModifierKind, Stat, StatsPlugin},
 };
 
+fn slimes(count: usize) -> EnemyGroup {
+    EnemyGroup(vec![
+        BattleEnemy {
+            name: "Slime".to_string(),
+            attributes: Attributes {
+                strength: 6.,
+                dexterity: 4.,
+                intelligence: 2.,
+                vitality: 4.,
+            },
+            experience: 40,
+        };
+        count
+    ])
+}
+
 fn get_game_areas() -> GameAreas {
     let passage_east = Passage::new(
         Transform::from_xyz(1280. / 2. - 15., 0., 1.),
@@ -26,20 +69,135 @@ fn get_game_areas() -> GameAreas {
         PassageDestination(0.into(), Transform::from_xyz(1280. / 2. - 75., 0., 1.)),
     );
     GameAreas::new(vec![
-        Area::new(Color::srgb(0.1, 0.1, 0.1), vec![passage_east]),
-        Area::new(Color::srgb_u8(0, 51, 0), vec![passage_west]),
+        Area::new(Color::srgb(0.1, 0.1, 0.1), vec![passage_east])
+            .with_name("Village")
+            .with_pickups(vec![PlacedPickup::new(
+                PickupId(0),
+                Transform::from_xyz(200., -200., 0.),
+                Sprite {
+                    color: Color::srgb(1., 0.4, 0.7),
+                    custom_size: Some(Vec2::new(20., 20.)),
+                    ..default()
+                },
+                Pickup::Item {
+                    item: 0.into(),
+                    count: 2,
+                },
+            )]),
+        Area::new(Color::srgb_u8(0, 51, 0), vec![passage_west])
+            .with_name("Meadow")
+            .with_encounters(vec![EncounterZone {
+                region: EncounterRegion::Rect(Rect::new(-640., 160., 640., 360.)),
+                table: EncounterTable(vec![(3, slimes(1)), (1, slimes(3))]),
+                step_distance: 120.,
+                chance: 0.2,
+            }])
+            .with_pickups(vec![PlacedPickup::new(
+                PickupId(1),
+                Transform::from_xyz(-200., -200., 0.),
+                Sprite {
+                    color: Color::srgb(0.3, 0.8, 0.3),
+                    custom_size: Some(Vec2::new(20., 20.)),
+                    ..default()
+                },
+                Pickup::Item {
+                    item: 2.into(),
+                    count: 4,
+                },
+            )]),
+    ])
+}
+
+fn get_item_database() -> ItemDatabase {
+    ItemDatabase::new(vec![
+        ItemDefinition {
+            id: 0.into(),
+            name: "Potion".to_string(),
+            icon: None,
+            stack_size: 10,
+            category: ItemCategory::Consumable,
+            use_effect: Some(UseEffect::Heal(30.)),
+            use_cooldown: 1.,
+            equipment: None,
+        },
+        ItemDefinition {
+            id: 1.into(),
+            name: "Swift boots".to_string(),
+            icon: None,
+            stack_size: 1,
+            category: ItemCategory::Equipment,
+            use_effect: None,
+            use_cooldown: 0.,
+            equipment: Some(Equippable {
+                slot: EquipmentSlot::Accessory,
+                modifiers: vec![(Stat::MoveSpeed, ModifierKind::Percent(0.3))],
+                collider_scale: 1.,
+                sprite: Some(EquippedSprite {
+                    color: [0.5, 0.3, 0.1, 1.],
+                    size: [60., 12.],
+                    offset: [0., -24.],
+                }),
+            }),
+        },
+        ItemDefinition {
+            id: 2.into(),
+            name: "Herb".to_string(),
+            icon: None,
+            stack_size: 20,
+            category: ItemCategory::Material,
+            use_effect: None,
+            use_cooldown: 0.,
+            equipment: None,
+        },
     ])
 }
 
 fn main() {
     App::new()
         .insert_resource(get_game_areas())
+        .insert_resource(get_item_database())
+        .insert_resource(RecipeBook::from_ron(include_str!("recipes.ron")).unwrap())
+        .insert_resource(DialogueLibrary::new(vec![DialogueTree::from_ron(
+            include_str!("dialogue/villager.ron"),
+        )
+        .unwrap()]))
+        .insert_resource(QuestDatabase::from_ron(include_str!("quests.ron")).unwrap())
         .add_plugins(DefaultPlugins)
         .add_plugins(PhysicsPlugin)
         .add_plugins(AreaPlugin)
         .add_plugins(PlayerPlugin)
+        .add_plugins(CombatPlugin)
+        .add_plugins(ProjectilePlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ProgressionPlugin)
+        .add_plugins(GameStatePlugin)
+        .add_plugins(MenuPlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(MapPlugin)
+        .add_plugins(ClockPlugin)
+        .add_plugins(NpcPlugin)
+        .add_plugins(BattlePlugin)
+        .add_plugins(EncounterPlugin)
+        .add_plugins(InventoryPlugin)
+        .add_plugins(PickupPlugin)
+        .add_plugins(EquipmentPlugin)
+        .add_plugins(ConsumablePlugin)
+        .add_plugins(LootPlugin)
+        .add_plugins(ShopPlugin)
+        .add_plugins(CraftingPlugin)
+        .add_plugins(InteractionPlugin)
+        .add_plugins(FlagsPlugin)
+        .add_plugins(DialoguePlugin)
+        .add_plugins(QuestPlugin)
+        .add_plugins(SavePlugin)
+        .add_plugins(AutosavePlugin)
         .add_systems(Startup, setup)
         .add_systems(Startup, create_enemy)
+        .add_systems(Startup, create_battle_enemy)
+        .add_systems(Startup, create_merchant)
+        .add_systems(Startup, create_cauldron)
+        .add_systems(Startup, create_villager)
+        .add_systems(Update, brew_potion)
         .run();
 }
 
@@ -56,9 +214,133 @@ fn create_enemy(mut commands: Commands) {
             ..Default::default()
         })
         .insert(AreaIdentifier(1))
+        .insert(Collider::cuboid(30., 30.))
+        .insert(RigidBody::Fixed)
+        .insert(Faction::Enemy)
+        .insert(Health::new(30.))
+        .insert(ContactDamage {
+            damage: 10.,
+            knockback: 600.,
+            lockout: 0.25,
+        })
+        .insert(ExperienceReward(100))
+        .insert(PersistentId(0))
+        .insert(LootTable(vec![
+            LootEntry::Guaranteed(LootDrop::Currency { min: 10, max: 25 }),
+            LootEntry::Chance {
+                chance: 0.5,
+                drop: LootDrop::Item {
+                    item: 0.into(),
+                    count: 1,
+                },
+            },
+        ]))
         .insert(Enemy);
 }
 
+fn create_battle_enemy(mut commands: Commands) {
+    commands
+        .spawn(SpriteBundle {
+            transform: Transform::from_xyz(-260., 200., 0.),
+            sprite: Sprite {
+                color: Color::srgb(0.6, 0., 0.6),
+                custom_size: Some(Vec2::new(40., 40.)),
+                ..default()
+            },
+            ..Default::default()
+        })
+        .insert(AreaIdentifier(0))
+        .insert(Collider::cuboid(20., 20.))
+        .insert(RigidBody::Fixed)
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(Encounter(slimes(2)))
+        .insert(Enemy);
+}
+
+fn create_merchant(mut commands: Commands) {
+    commands
+        .spawn(SpriteBundle {
+            transform: Transform::from_xyz(0., 250., 0.),
+            sprite: Sprite {
+                color: Color::srgb(0.9, 0.7, 0.2),
+                custom_size: Some(Vec2::new(50., 50.)),
+                ..default()
+            },
+            ..Default::default()
+        })
+        .insert(AreaIdentifier(0))
+        .insert(Collider::cuboid(25., 25.))
+        .insert(RigidBody::Fixed)
+        .insert(Interactable::new("Trade", 100.))
+        .insert(Merchant::new(
+            vec![
+                StockEntry::new(0.into(), 15, 5, 5),
+                StockEntry::new(1.into(), 120, 40, 1),
+            ],
+            120.,
+        ));
+}
+
+fn create_villager(mut commands: Commands) {
+    commands
+        .spawn(SpriteBundle {
+            transform: Transform::from_xyz(-250., -100., 0.),
+            sprite: Sprite {
+                color: Color::srgb(0.2, 0.7, 0.9),
+                custom_size: Some(Vec2::new(40., 50.)),
+                ..default()
+            },
+            ..Default::default()
+        })
+        .insert(AreaIdentifier(0))
+        .insert(Collider::cuboid(20., 25.))
+        .insert(RigidBody::KinematicPositionBased)
+        .insert(Interactable::new("Talk", 100.))
+        .insert(Dialogue("villager".to_string()))
+        .insert(Npc)
+        .insert(NpcSchedule::new(vec![
+            ScheduleEntry::new(8., AreaIdentifier(0), Vec2::new(-250., -100.)),
+            ScheduleEntry::new(12., AreaIdentifier(1), Vec2::new(0., -150.)),
+            ScheduleEntry::new(17., AreaIdentifier(0), Vec2::new(-250., -100.)),
+        ]));
+}
+
+fn create_cauldron(mut commands: Commands) {
+    commands
+        .spawn(SpriteBundle {
+            transform: Transform::from_xyz(-300., 0., 0.),
+            sprite: Sprite {
+                color: Color::srgb(0.3, 0.3, 0.35),
+                custom_size: Some(Vec2::new(40., 40.)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        })
+        .insert(AreaIdentifier(1))
+        .insert(Interactable::new("Brew potion", 80.))
+        .insert(CraftingStation("Cauldron".to_string()));
+}
+
+fn brew_potion(
+    mut ev_interacted: EventReader<Interacted>,
+    station_query: Query<(), With<CraftingStation>>,
+    player_query: Query<Entity, With<Player>>,
+    mut ev_craft: EventWriter<Craft>,
+) {
+    for interacted in ev_interacted.read() {
+        if !station_query.contains(interacted.entity) {
+            continue;
+        }
+        for entity in &player_query {
+            ev_craft.send(Craft {
+                entity,
+                recipe: RecipeId(0),
+            });
+        }
+    }
+}
+
 fn setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
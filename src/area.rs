@@ -1,11 +1,21 @@
+use std::collections::{HashMap, VecDeque};
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
 
+use crate::encounter::EncounterZone;
+use crate::pickup::{CollectedPickups, PickupId, PlacedPickup};
 use crate::player::Player;
+use crate::progression::Level;
+use crate::state::is_playing;
 
 pub struct AreaPlugin;
 
-#[derive(Clone, Component, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Component, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct AreaIdentifier(pub usize);
 
 impl From<usize> for AreaIdentifier {
@@ -14,6 +24,9 @@ impl From<usize> for AreaIdentifier {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct CurrentArea(pub AreaIdentifier);
+
 #[derive(Resource)]
 pub struct GameAreas {
     areas: Vec<Area>,
@@ -23,27 +36,89 @@ impl GameAreas {
     pub fn new(areas: Vec<Area>) -> Self {
         Self { areas }
     }
+
+    /// Parses a list of [`AreaData`] written in RON, with the same extensions as
+    /// [`DialogueTree::from_ron`](crate::dialogue::DialogueTree::from_ron).
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME)
+            .from_str::<Vec<AreaData>>(source)
+            .map(|areas| GameAreas::new(areas.into_iter().map(Area::from).collect()))
+    }
+
+    pub fn get(&self, identifier: AreaIdentifier) -> Option<&Area> {
+        self.areas.get(identifier.0)
+    }
+
+    /// First passage on a shortest route between two areas, ignoring level requirements.
+    pub fn next_passage(&self, from: AreaIdentifier, to: AreaIdentifier) -> Option<&Passage> {
+        let mut first_passages: HashMap<AreaIdentifier, &Passage> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(area) = queue.pop_front() {
+            for passage in self.get(area).map_or(&[][..], Area::passages) {
+                let destination = passage.destination.0;
+                if destination == from || first_passages.contains_key(&destination) {
+                    continue;
+                }
+                let first = first_passages.get(&area).copied().unwrap_or(passage);
+                if destination == to {
+                    return Some(first);
+                }
+                first_passages.insert(destination, first);
+                queue.push_back(destination);
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AreaIdentifier, &Area)> {
+        self.areas
+            .iter()
+            .enumerate()
+            .map(|(i, area)| (AreaIdentifier(i), area))
+    }
 }
 
+/// Moves the player to the destination, loading its area.
 #[derive(Clone, Copy, Debug, Event, PartialEq)]
-struct AreaTransitionEvent(PassageDestination);
+pub struct AreaTransitionEvent(pub PassageDestination);
+
+/// Area loaded at startup instead of the first one, e.g. to continue from a save.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct StartingArea(pub AreaIdentifier);
 
-fn area_startup_system(
+/// Sent once the area identified by the payload has been loaded.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct AreaEntered(pub AreaIdentifier);
+
+pub(crate) fn area_startup_system(
     mut commands: Commands,
     mut background: ResMut<ClearColor>,
     game_areas: Res<GameAreas>,
+    collected_pickups: Option<Res<CollectedPickups>>,
+    starting_area: Option<Res<StartingArea>>,
+    mut ev_area_entered: EventWriter<AreaEntered>,
 ) {
-    game_areas.areas[0].load(&mut commands, &mut background);
+    let mut area = starting_area.map_or(AreaIdentifier(0), |starting_area| starting_area.0);
+    if game_areas.get(area).is_none() {
+        error!("Starting area {} doesn't exist, starting in area 0", area.0);
+        area = AreaIdentifier(0);
+    }
+    game_areas.areas[area.0].load(&mut commands, &mut background, collected_pickups.as_deref());
+    commands.insert_resource(CurrentArea(area));
+    ev_area_entered.send(AreaEntered(area));
 }
 
 impl Plugin for AreaPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ClearColor(Color::srgb(1., 0., 0.)))
             .add_event::<AreaTransitionEvent>()
+            .add_event::<AreaEntered>()
             .add_systems(Startup, area_startup_system)
-            .add_systems(Update, area_transition_check)
+            .add_systems(Update, area_transition_check.run_if(is_playing))
             .add_systems(Update, area_transition)
-            .add_systems(Update, area_transition_drawing);
+            .add_systems(Update, area_transition_drawing)
+            .add_systems(Update, area_collider_scoping);
     }
 }
 
@@ -52,6 +127,7 @@ pub struct Passage {
     transform: Transform,
     sprite: Sprite,
     destination: PassageDestination,
+    required_level: Option<RequiredLevel>,
 }
 
 #[derive(Bundle)]
@@ -68,9 +144,27 @@ impl Passage {
             transform,
             sprite,
             destination,
+            required_level: None,
         }
     }
 
+    pub fn with_required_level(mut self, level: Level) -> Self {
+        self.required_level = Some(RequiredLevel(level));
+        self
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn required_level(&self) -> Option<Level> {
+        self.required_level.map(|required_level| required_level.0)
+    }
+
+    pub fn destination(&self) -> &PassageDestination {
+        &self.destination
+    }
+
     fn bundle(&self) -> PassageBundle {
         PassageBundle {
             sprite: SpriteBundle {
@@ -93,43 +187,157 @@ impl PartialEq for Passage {
         self.transform == other.transform
             && self.sprite.custom_size == other.sprite.custom_size
             && self.destination == other.destination
+            && self.required_level == other.required_level
     }
 }
 
-#[derive(Clone, Component, Copy, Debug, PartialEq)]
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct PassageDestination(pub AreaIdentifier, pub Transform);
 
+/// An area as written in area data files. Encounters and pickups are added in code.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AreaData {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub color: Color,
+    #[serde(default)]
+    pub passages: Vec<PassageData>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PassageData {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub color: Color,
+    pub destination: AreaIdentifier,
+    /// Where the player is placed in the destination area.
+    pub arrival: Vec2,
+    #[serde(default)]
+    pub required_level: Option<Level>,
+}
+
+impl From<PassageData> for Passage {
+    fn from(data: PassageData) -> Self {
+        Passage {
+            transform: Transform::from_translation(data.position.extend(1.)),
+            sprite: Sprite {
+                color: data.color,
+                custom_size: Some(data.size),
+                ..default()
+            },
+            destination: PassageDestination(
+                data.destination,
+                Transform::from_translation(data.arrival.extend(1.)),
+            ),
+            required_level: data.required_level.map(RequiredLevel),
+        }
+    }
+}
+
+impl From<AreaData> for Area {
+    fn from(data: AreaData) -> Self {
+        Area {
+            name: data.name,
+            color: data.color,
+            passages: data.passages.into_iter().map(Passage::from).collect(),
+            encounters: Vec::new(),
+            pickups: Vec::new(),
+        }
+    }
+}
+
+/// Minimum player level needed to use a passage.
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub struct RequiredLevel(pub Level);
+
 #[derive(Clone)]
 pub struct Area {
+    name: Option<String>,
     color: Color,
     passages: Vec<Passage>,
+    encounters: Vec<EncounterZone>,
+    pickups: Vec<PlacedPickup>,
 }
 
 impl Area {
     pub fn new(color: Color, passages: Vec<Passage>) -> Self {
-        Area { color, passages }
+        Area {
+            name: None,
+            color,
+            passages,
+            encounters: Vec::new(),
+            pickups: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn with_pickups(mut self, pickups: Vec<PlacedPickup>) -> Self {
+        self.pickups = pickups;
+        self
+    }
+
+    pub fn with_encounters(mut self, encounters: Vec<EncounterZone>) -> Self {
+        self.encounters = encounters;
+        self
+    }
+
+    pub fn passages(&self) -> &[Passage] {
+        &self.passages
+    }
+
+    pub fn encounter_zones(&self) -> &[EncounterZone] {
+        &self.encounters
     }
 
-    fn load(&self, commands: &mut Commands, background: &mut ResMut<ClearColor>) {
+    fn load(
+        &self,
+        commands: &mut Commands,
+        background: &mut ResMut<ClearColor>,
+        collected_pickups: Option<&CollectedPickups>,
+    ) {
         background.0 = self.color;
+        for pickup in &self.pickups {
+            if !collected_pickups.is_some_and(|collected| collected.0.contains(&pickup.id())) {
+                commands.spawn(pickup.bundle());
+            }
+        }
         for passage in &self.passages {
-            commands.spawn(passage.bundle());
+            let mut entity = commands.spawn(passage.bundle());
+            if let Some(required_level) = passage.required_level {
+                entity.insert(required_level);
+            }
         }
     }
 }
 
 fn area_transition_check(
     mut collision_events: EventReader<CollisionEvent>,
-    player_query: Query<Entity, With<Player>>,
-    passage_destinations: Query<&PassageDestination>,
+    player_query: Query<Option<&Level>, With<Player>>,
+    passage_destinations: Query<(&PassageDestination, Option<&RequiredLevel>)>,
     mut ev_area_transition: EventWriter<AreaTransitionEvent>,
 ) {
+    let passage_if_allowed = |player, passage| {
+        let player_level = player_query.get(player).ok()?.copied().unwrap_or_default();
+        let (destination, required_level) = passage_destinations.get(passage).ok()?;
+        match required_level {
+            Some(required_level) if player_level < required_level.0 => None,
+            _ => Some(destination),
+        }
+    };
     let mut area_transition_events = collision_events.read().filter_map(|x| {
         if let CollisionEvent::Started(l, r, _) = x {
             if player_query.contains(*l) && passage_destinations.contains(*r) {
-                passage_destinations.get(*r).ok()
+                passage_if_allowed(*l, *r)
             } else if player_query.contains(*r) && passage_destinations.contains(*l) {
-                passage_destinations.get(*l).ok()
+                passage_if_allowed(*r, *l)
             } else {
                 None
             }
@@ -145,23 +353,36 @@ fn area_transition_check(
     }
 }
 
-fn area_transition(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn area_transition(
     mut commands: Commands,
     mut player_query: Query<(&Player, &mut Transform)>,
     mut ev_area_transition: EventReader<AreaTransitionEvent>,
     game_areas: Res<GameAreas>,
     mut background: ResMut<ClearColor>,
     passages: Query<(Entity, &PassageDestination)>,
+    placed_pickups: Query<Entity, With<PickupId>>,
+    collected_pickups: Option<Res<CollectedPickups>>,
+    mut ev_area_entered: EventWriter<AreaEntered>,
 ) {
     if let Some(destination) = ev_area_transition.read().next() {
         assert!(destination.0 .0 .0 < game_areas.areas.len());
         for passage in passages.iter() {
             commands.entity(passage.0).despawn();
         }
+        for pickup in placed_pickups.iter() {
+            commands.entity(pickup).despawn_recursive();
+        }
         if let Ok((_, mut transform)) = player_query.get_single_mut() {
             transform.translation = destination.0 .1.translation;
         }
-        game_areas.areas[destination.0 .0 .0].load(&mut commands, &mut background);
+        game_areas.areas[destination.0 .0 .0].load(
+            &mut commands,
+            &mut background,
+            collected_pickups.as_deref(),
+        );
+        commands.insert_resource(CurrentArea(destination.0 .0));
+        ev_area_entered.send(AreaEntered(destination.0 .0));
     }
 }
 
@@ -181,13 +402,36 @@ fn area_transition_drawing(
     }
 }
 
+// Entities tagged with an area keep existing while the player is elsewhere, so their colliders
+// have to be switched off to keep them from interacting with the area that is actually loaded.
+fn area_collider_scoping(
+    mut commands: Commands,
+    current_area: Option<Res<CurrentArea>>,
+    colliders: Query<(Entity, &AreaIdentifier, Has<ColliderDisabled>), With<Collider>>,
+) {
+    let Some(current_area) = current_area else {
+        return;
+    };
+    for (entity, &area, disabled) in colliders.iter() {
+        if area == current_area.0 && disabled {
+            commands.entity(entity).remove::<ColliderDisabled>();
+        } else if area != current_area.0 && !disabled {
+            commands.entity(entity).insert(ColliderDisabled);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        Area, AreaIdentifier, AreaTransitionEvent, GameAreas, Passage, PassageDestination,
+        Area, AreaEntered, AreaIdentifier, AreaTransitionEvent, CurrentArea, GameAreas, Passage,
+        PassageDestination, RequiredLevel,
     };
     use crate::enemy::Enemy;
+    use crate::item::ItemId;
+    use crate::pickup::{CollectedPickups, Pickup, PickupId, PlacedPickup};
     use crate::player::{self, Player};
+    use crate::progression::Level;
     use bevy::prelude::*;
     use bevy::utils::default;
     use bevy_rapier2d::plugin::{NoUserData, RapierPhysicsPlugin};
@@ -289,6 +533,52 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn passages_require_player_level() {
+        let mut app = get_test_app();
+        let passage = Passage::new(
+            Transform::from_xyz(12., 12., 100.),
+            Sprite {
+                custom_size: Some(Vec2::new(2., 2.)),
+                ..default()
+            },
+            PassageDestination(1.into(), Transform::from_xyz(20., 20., 2.)),
+        )
+        .with_required_level(Level(3));
+        app.insert_resource(GameAreas::new(vec![Area::new(Color::BLACK, vec![passage])]));
+        app.insert_resource(ClearColor::default());
+        app.add_event::<AreaEntered>();
+        app.add_systems(Startup, super::area_startup_system);
+        app.add_plugins(player::test_utils::PlayerPlugin);
+        app.update();
+        let required_levels: Vec<RequiredLevel> = app
+            .world_mut()
+            .query::<&RequiredLevel>()
+            .iter(app.world())
+            .copied()
+            .collect();
+        assert_eq!(required_levels, vec![RequiredLevel(Level(3))]);
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .iter(app.world())
+            .next()
+            .unwrap();
+        app.world_mut()
+            .entity_mut(player)
+            .insert(Transform::from_xyz(10., 10., 1.))
+            .insert(Level(2));
+        app.world_mut()
+            .insert_resource(Events::<AreaTransitionEvent>::default());
+        app.add_systems(Last, super::area_transition_check);
+        app.update();
+        let area_transition_events = app
+            .world()
+            .get_resource::<Events<AreaTransitionEvent>>()
+            .unwrap();
+        assert!(area_transition_events.is_empty());
+    }
+
     fn get_test_areas() -> GameAreas {
         let passage_out1 = Passage {
             transform: Transform::from_xyz(12., 12., 100.),
@@ -298,6 +588,7 @@ mod tests {
                 ..default()
             },
             destination: PassageDestination(1.into(), Transform::from_xyz(20., 20., 2.)),
+            required_level: None,
         };
         let passage_out2 = Passage {
             transform: Transform::from_xyz(92., 92., 100.),
@@ -307,6 +598,7 @@ mod tests {
                 ..default()
             },
             destination: PassageDestination(1.into(), Transform::from_xyz(50., 50., 5.)),
+            required_level: None,
         };
         let passage_in1 = Passage {
             transform: Transform::from_xyz(60., 60., 100.),
@@ -316,6 +608,7 @@ mod tests {
                 ..default()
             },
             destination: PassageDestination(0.into(), Transform::from_xyz(40., 40., 8.)),
+            required_level: None,
         };
         let passage_in2 = Passage {
             transform: Transform::from_xyz(160., 160., 100.),
@@ -325,16 +618,23 @@ mod tests {
                 ..default()
             },
             destination: PassageDestination(0.into(), Transform::from_xyz(140., 140., 18.)),
+            required_level: None,
         };
         GameAreas {
             areas: [
                 Area {
+                    name: None,
                     color: Color::srgb(0.125, 0.82, 0.325),
                     passages: vec![passage_out1, passage_out2],
+                    encounters: Vec::new(),
+                    pickups: Vec::new(),
                 },
                 Area {
+                    name: None,
                     color: Color::srgb(0.251, 0.521, 0.382),
                     passages: vec![passage_in1, passage_in2],
+                    encounters: Vec::new(),
+                    pickups: Vec::new(),
                 },
             ]
             .into(),
@@ -346,14 +646,20 @@ mod tests {
     fn check_area_is_loaded(world: &mut World, area: &Area) {
         assert_eq!(area.color, world.get_resource::<ClearColor>().unwrap().0);
         let mut expected_passages = area.passages.clone();
-        let mut passage_query = world.query::<(&PassageDestination, &Transform, &Sprite)>();
-        for (dest, transform, sprite) in passage_query.iter(world) {
+        let mut passage_query = world.query::<(
+            &PassageDestination,
+            &Transform,
+            &Sprite,
+            Option<&RequiredLevel>,
+        )>();
+        for (dest, transform, sprite, required_level) in passage_query.iter(world) {
             let pos = expected_passages.iter().position(|passage| {
                 *passage
                     == Passage {
                         transform: *transform,
                         sprite: sprite.clone(),
                         destination: *dest,
+                        required_level: required_level.copied(),
                     }
             });
             assert!(pos.is_some());
@@ -377,6 +683,7 @@ mod tests {
         }
         app.world_mut()
             .insert_resource(Events::<AreaTransitionEvent>::default());
+        app.add_event::<AreaEntered>();
         app.world_mut().insert_resource(ClearColor::default());
         app.add_systems(Startup, super::area_startup_system);
         app.add_systems(
@@ -399,6 +706,46 @@ mod tests {
             .clone();
         assert_eq!(*player.1, areas[0].passages[0].destination.1);
         check_area_is_loaded(app.world_mut(), &areas[1]);
+        assert_eq!(
+            app.world().resource::<CurrentArea>(),
+            &CurrentArea(1.into())
+        );
+    }
+
+    #[test]
+    fn collected_pickups_are_not_reloaded() {
+        let mut app = get_test_app();
+        let placed_pickup = |id| {
+            PlacedPickup::new(
+                PickupId(id),
+                Transform::from_xyz(100., 100., 1.),
+                Sprite {
+                    custom_size: Some(Vec2::new(4., 4.)),
+                    ..default()
+                },
+                Pickup::Item {
+                    item: ItemId(0),
+                    count: 1,
+                },
+            )
+        };
+        app.insert_resource(GameAreas::new(vec![
+            Area::new(Color::BLACK, vec![]).with_pickups(vec![placed_pickup(0), placed_pickup(1)])
+        ]));
+        let mut collected = CollectedPickups::default();
+        collected.0.insert(PickupId(1));
+        app.insert_resource(collected);
+        app.insert_resource(ClearColor::default());
+        app.add_event::<AreaEntered>();
+        app.add_systems(Startup, super::area_startup_system);
+        app.update();
+        let ids: Vec<PickupId> = app
+            .world_mut()
+            .query_filtered::<&PickupId, With<Pickup>>()
+            .iter(app.world())
+            .copied()
+            .collect();
+        assert_eq!(ids, vec![PickupId(0)]);
     }
 
     #[test]
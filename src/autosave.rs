@@ -0,0 +1,341 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::area::{area_transition, AreaEntered, StartingArea};
+use crate::player::Player;
+use crate::save::{
+    capture, load_game, restore_player, restore_world, GameLoaded, PlayerComponents, PlayerState,
+    SaveData, SaveMigrations, SaveSettings, WorldResources,
+};
+
+pub struct AutosavePlugin;
+
+#[derive(Clone, Debug, Resource)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// Number of autosaves kept before the oldest one is overwritten.
+    pub slots: usize,
+    /// Continue from the newest autosave when the game starts.
+    pub restore_on_startup: bool,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        AutosaveSettings {
+            enabled: true,
+            slots: 3,
+            restore_on_startup: false,
+        }
+    }
+}
+
+/// Autosave slot that was written last.
+pub fn newest_autosave(settings: &SaveSettings, slots: usize) -> Option<usize> {
+    (0..slots)
+        .filter_map(|slot| {
+            let modified = fs::metadata(settings.autosave_path(slot))
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((slot, modified))
+        })
+        .max_by_key(|(_, modified)| *modified)
+        .map(|(slot, _)| slot)
+}
+
+/// Slot the next autosave is written to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct NextAutosave(pub usize);
+
+/// Player state of the autosave read at startup, applied once the player exists. World state is
+/// applied right away so that the starting area is loaded with it.
+#[derive(Resource)]
+struct PendingRestore(PlayerState);
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct Autosaved {
+    pub slot: usize,
+}
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutosaveSettings>()
+            .init_resource::<NextAutosave>()
+            .init_resource::<SaveSettings>()
+            .init_resource::<SaveMigrations>()
+            .add_event::<Autosaved>()
+            .add_event::<AreaEntered>()
+            .add_event::<GameLoaded>()
+            .add_systems(PreStartup, read_autosaves)
+            .add_systems(
+                Update,
+                apply_pending_restore.run_if(resource_exists::<PendingRestore>),
+            )
+            .add_systems(
+                Update,
+                autosave
+                    .after(area_transition)
+                    .after(load_game)
+                    .after(apply_pending_restore),
+            );
+    }
+}
+
+fn read_autosaves(
+    mut commands: Commands,
+    settings: Res<SaveSettings>,
+    autosave_settings: Res<AutosaveSettings>,
+    migrations: Res<SaveMigrations>,
+    mut world_resources: WorldResources,
+) {
+    let Some(newest) = newest_autosave(&settings, autosave_settings.slots) else {
+        return;
+    };
+    commands.insert_resource(NextAutosave((newest + 1) % autosave_settings.slots));
+    if !autosave_settings.restore_on_startup {
+        return;
+    }
+    match SaveData::read_from(&settings.autosave_path(newest), &migrations.0) {
+        Ok(data) => {
+            commands.insert_resource(StartingArea(data.area));
+            commands.insert_resource(PendingRestore(data.player));
+            restore_world(data.world, &mut world_resources);
+        }
+        Err(error) => error!("Couldn't restore autosave {newest}: {error}"),
+    }
+}
+
+fn apply_pending_restore(
+    mut commands: Commands,
+    pending: Res<PendingRestore>,
+    mut player_query: Query<PlayerComponents, With<Player>>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+    let player = pending.0.clone();
+    commands.remove_resource::<PendingRestore>();
+    if let Err(error) = restore_player(player, &mut player_query) {
+        error!("Couldn't restore autosave: {error}");
+    }
+}
+
+// The starting area and areas reloaded by loading a save aren't autosaved.
+#[allow(clippy::too_many_arguments)]
+fn autosave(
+    mut ev_area_entered: EventReader<AreaEntered>,
+    mut ev_loaded: EventReader<GameLoaded>,
+    mut started: Local<bool>,
+    mut loading: Local<bool>,
+    settings: Res<SaveSettings>,
+    autosave_settings: Res<AutosaveSettings>,
+    mut next: ResMut<NextAutosave>,
+    player_query: Query<PlayerComponents, With<Player>>,
+    world_resources: WorldResources,
+    mut ev_autosaved: EventWriter<Autosaved>,
+) {
+    if ev_loaded.read().last().is_some() {
+        *loading = true;
+    }
+    let Some(entered) = ev_area_entered.read().last().copied() else {
+        return;
+    };
+    if !std::mem::replace(&mut *started, true) || std::mem::take(&mut *loading) {
+        return;
+    }
+    if !autosave_settings.enabled || autosave_settings.slots == 0 {
+        return;
+    }
+    let slot = next.0 % autosave_settings.slots;
+    let result = capture(entered.0, &player_query, &world_resources)
+        .and_then(|data| data.write_to(&settings.autosave_path(slot)));
+    if let Err(error) = result {
+        error!("Couldn't autosave: {error}");
+        return;
+    }
+    next.0 = (slot + 1) % autosave_settings.slots;
+    ev_autosaved.send(Autosaved { slot });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newest_autosave, AutosavePlugin, AutosaveSettings, NextAutosave};
+    use crate::area::{
+        area_startup_system, area_transition, Area, AreaEntered, AreaIdentifier,
+        AreaTransitionEvent, CurrentArea, GameAreas, PassageDestination, StartingArea,
+    };
+    use crate::combat::{Defeated, Health};
+    use crate::crafting::KnownRecipes;
+    use crate::equipment::Equipment;
+    use crate::inventory::Inventory;
+    use crate::pickup::{CollectedPickups, Pickup, PickupId, PlacedPickup};
+    use crate::player::Player;
+    use crate::progression::{Experience, Level};
+    use crate::quest::QuestLog;
+    use crate::save::test_utils::temp_directory;
+    use crate::save::{save_migrations, LoadGame, SaveData, SaveGame, SavePlugin, SaveSettings};
+    use crate::stats::Attributes;
+    use crate::wallet::Wallet;
+    use bevy::prelude::*;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    fn get_test_app(settings: SaveSettings, autosave_settings: AutosaveSettings) -> App {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_event::<Defeated>();
+        app.add_event::<AreaTransitionEvent>();
+        app.insert_resource(settings);
+        app.insert_resource(autosave_settings);
+        app.insert_resource(CurrentArea(0.into()));
+        let placed_pickup = |id| {
+            PlacedPickup::new(
+                PickupId(id),
+                Transform::default(),
+                Sprite {
+                    custom_size: Some(Vec2::new(4., 4.)),
+                    ..default()
+                },
+                Pickup::Currency(1),
+            )
+        };
+        app.insert_resource(GameAreas::new(vec![
+            Area::new(Color::BLACK, vec![]),
+            Area::new(Color::BLACK, vec![]).with_pickups(vec![placed_pickup(0), placed_pickup(1)]),
+            Area::new(Color::BLACK, vec![]),
+        ]));
+        app.insert_resource(ClearColor::default());
+        app.add_event::<AreaEntered>();
+        app.add_systems(Startup, area_startup_system);
+        app.add_systems(Update, area_transition);
+        app.add_plugins(SavePlugin);
+        app.add_plugins(AutosavePlugin);
+        app.world_mut()
+            .spawn(Player::default())
+            .insert(Transform::default())
+            .insert(Health::new(80.))
+            .insert(Attributes::default())
+            .insert(Experience(0))
+            .insert(Level(1))
+            .insert(Inventory::new(4))
+            .insert(Equipment::default())
+            .insert(Wallet(0))
+            .insert(KnownRecipes::default())
+            .insert(QuestLog::default());
+        app.update();
+        app
+    }
+
+    fn transition(app: &mut App, area: usize) {
+        app.world_mut()
+            .send_event(AreaTransitionEvent(PassageDestination(
+                area.into(),
+                Transform::from_xyz(area as f32 * 10., 0., 0.),
+            )));
+        app.update();
+    }
+
+    fn autosave_area(settings: &SaveSettings, slot: usize) -> AreaIdentifier {
+        SaveData::read_from(&settings.autosave_path(slot), &save_migrations())
+            .unwrap()
+            .area
+    }
+
+    #[test]
+    fn autosaves_rotate_on_transitions() {
+        let settings = SaveSettings {
+            directory: temp_directory("autosave_rotate"),
+        };
+        let mut app = get_test_app(
+            settings.clone(),
+            AutosaveSettings {
+                slots: 2,
+                ..default()
+            },
+        );
+        assert_eq!(newest_autosave(&settings, 2), None);
+        for area in [1, 2, 0] {
+            transition(&mut app, area);
+        }
+        assert_eq!(autosave_area(&settings, 0), AreaIdentifier(0));
+        assert_eq!(autosave_area(&settings, 1), AreaIdentifier(2));
+        assert_eq!(app.world().resource::<NextAutosave>(), &NextAutosave(1));
+
+        // Loading a save reloads its area without autosaving.
+        app.world_mut().send_event(SaveGame { slot: 0 });
+        app.update();
+        app.world_mut().send_event(LoadGame { slot: 0 });
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<NextAutosave>(), &NextAutosave(1));
+        assert_eq!(autosave_area(&settings, 1), AreaIdentifier(2));
+        let _ = std::fs::remove_dir_all(&settings.directory);
+
+        let mut app = get_test_app(
+            settings.clone(),
+            AutosaveSettings {
+                enabled: false,
+                ..default()
+            },
+        );
+        transition(&mut app, 1);
+        assert_eq!(newest_autosave(&settings, 3), None);
+    }
+
+    #[test]
+    fn newest_autosave_is_restored_at_startup() {
+        let settings = SaveSettings {
+            directory: temp_directory("autosave_restore"),
+        };
+        let mut app = get_test_app(settings.clone(), AutosaveSettings::default());
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(app.world());
+        app.world_mut().get_mut::<Wallet>(player).unwrap().0 = 10;
+        app.world_mut()
+            .resource_mut::<CollectedPickups>()
+            .0
+            .insert(PickupId(0));
+        transition(&mut app, 1);
+        app.world_mut().get_mut::<Wallet>(player).unwrap().0 = 20;
+        transition(&mut app, 2);
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        File::options()
+            .write(true)
+            .open(settings.autosave_path(1))
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+
+        let mut app = get_test_app(
+            settings.clone(),
+            AutosaveSettings {
+                restore_on_startup: true,
+                ..default()
+            },
+        );
+        app.update();
+        assert_eq!(
+            app.world().resource::<StartingArea>(),
+            &StartingArea(AreaIdentifier(1))
+        );
+        assert_eq!(app.world().resource::<NextAutosave>(), &NextAutosave(1));
+        let (transform, wallet) = app
+            .world_mut()
+            .query_filtered::<(&Transform, &Wallet), With<Player>>()
+            .single(app.world());
+        assert_eq!(wallet, &Wallet(10));
+        assert_eq!(transform.translation, Vec3::new(10., 0., 0.));
+        // The starting area is loaded without the pickups collected before the autosave.
+        let pickups: Vec<PickupId> = app
+            .world_mut()
+            .query_filtered::<&PickupId, With<Pickup>>()
+            .iter(app.world())
+            .copied()
+            .collect();
+        assert_eq!(pickups, vec![PickupId(1)]);
+        let _ = std::fs::remove_dir_all(&settings.directory);
+    }
+}
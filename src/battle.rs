@@ -0,0 +1,761 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::combat::{apply_damage, Defeated, Faction, Health};
+use crate::inventory::Inventory;
+use crate::item::{ItemDatabase, ItemId, UseEffect};
+use crate::player::Player;
+use crate::progression::ExperienceGained;
+use crate::state::{is_playing, GameState};
+use crate::stats::{Attributes, DerivedStats, StatModifiers};
+
+/// Turn-based battles as an alternative to real-time combat. Requires
+/// [`GameStatePlugin`](crate::state::GameStatePlugin).
+pub struct BattlePlugin;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleEnemy {
+    pub name: String,
+    pub attributes: Attributes,
+    pub experience: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnemyGroup(pub Vec<BattleEnemy>);
+
+/// Starts a battle against the given group when the player touches this entity.
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct Encounter(pub EnemyGroup);
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct EncounterEvent {
+    pub group: EnemyGroup,
+    /// Overworld entity that started the encounter. It is [`Defeated`] if the battle is won.
+    pub source: Option<Entity>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Combatant {
+    pub name: String,
+    pub faction: Faction,
+    pub stats: DerivedStats,
+    pub initiative: f32,
+    pub intelligence: f32,
+    pub health: Health,
+    pub experience: u32,
+}
+
+impl Combatant {
+    pub fn is_alive(&self) -> bool {
+        !self.health.is_depleted()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BattleCommand {
+    Attack {
+        target: usize,
+    },
+    Skill {
+        target: usize,
+        power: f32,
+    },
+    /// Uses one of the player's [`BattleItem`]s.
+    Item {
+        item: ItemId,
+    },
+    Flee,
+}
+
+/// Healing item the player brought into battle. Used items are taken from the player's
+/// inventory when the battle ends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BattleItem {
+    pub item: ItemId,
+    pub heal: f32,
+    pub count: u32,
+    used: u32,
+}
+
+impl BattleItem {
+    pub fn new(item: ItemId, heal: f32, count: u32) -> Self {
+        BattleItem {
+            item,
+            heal,
+            count,
+            used: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BattleOutcome {
+    Victory,
+    Defeat,
+    Fled,
+}
+
+/// Command for the combatant whose turn it currently is.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct BattleCommandEvent(pub BattleCommand);
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct BattleFinished(pub BattleOutcome);
+
+#[derive(Debug, Resource)]
+pub struct Battle {
+    pub combatants: Vec<Combatant>,
+    items: Vec<BattleItem>,
+    turn_queue: VecDeque<usize>,
+    outcome: Option<BattleOutcome>,
+    source: Option<Entity>,
+}
+
+impl Battle {
+    pub fn new(combatants: Vec<Combatant>) -> Self {
+        let mut battle = Battle {
+            combatants,
+            items: Vec::new(),
+            turn_queue: VecDeque::new(),
+            outcome: None,
+            source: None,
+        };
+        battle.fill_turn_queue();
+        battle
+    }
+
+    pub fn with_items(mut self, items: Vec<BattleItem>) -> Self {
+        self.items = items;
+        self
+    }
+
+    pub fn items(&self) -> &[BattleItem] {
+        &self.items
+    }
+
+    /// First item the player still has left.
+    pub fn usable_item(&self) -> Option<&BattleItem> {
+        self.items.iter().find(|item| item.count > 0)
+    }
+
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        self.outcome
+    }
+
+    /// Index of the combatant whose turn it is.
+    pub fn current(&self) -> Option<usize> {
+        self.turn_queue.front().copied()
+    }
+
+    fn fill_turn_queue(&mut self) {
+        let mut order: Vec<usize> = (0..self.combatants.len())
+            .filter(|&i| self.combatants[i].is_alive())
+            .collect();
+        order.sort_by(|&a, &b| {
+            self.combatants[b]
+                .initiative
+                .total_cmp(&self.combatants[a].initiative)
+        });
+        self.turn_queue = order.into();
+    }
+
+    fn side_defeated(&self, faction: Faction) -> bool {
+        self.combatants
+            .iter()
+            .filter(|combatant| combatant.faction == faction)
+            .all(|combatant| !combatant.is_alive())
+    }
+
+    fn hit(&mut self, target: usize, base: f32) {
+        let Some(target) = self.combatants.get_mut(target) else {
+            return;
+        };
+        let amount = base * 100. / (100. + target.stats.defense);
+        target.health.current = (target.health.current - amount).max(0.);
+    }
+
+    /// Carries out a command for the current combatant and advances to the next turn. Using an
+    /// item that has run out is refused and doesn't take up the turn.
+    pub fn execute(&mut self, command: BattleCommand) {
+        if self.outcome.is_some() {
+            return;
+        }
+        if let BattleCommand::Item { item } = command {
+            if !self.items.iter().any(|i| i.item == item && i.count > 0) {
+                return;
+            }
+        }
+        let Some(actor) = self.turn_queue.pop_front() else {
+            return;
+        };
+        let attacker = self.combatants[actor].clone();
+        match command {
+            BattleCommand::Attack { target } => {
+                self.hit(target, 10. + attacker.stats.attack * 2.);
+            }
+            BattleCommand::Skill { target, power } => {
+                self.hit(target, (10. + attacker.intelligence * 2.) * power);
+            }
+            BattleCommand::Item { item } => {
+                if let Some(item) = self.items.iter_mut().find(|i| i.item == item) {
+                    item.count -= 1;
+                    item.used += 1;
+                    let health = &mut self.combatants[actor].health;
+                    health.current = (health.current + item.heal).min(health.max);
+                }
+            }
+            BattleCommand::Flee => {
+                let fastest_opponent = self
+                    .combatants
+                    .iter()
+                    .filter(|c| c.faction != attacker.faction && c.is_alive())
+                    .map(|c| c.initiative)
+                    .fold(f32::MIN, f32::max);
+                if attacker.initiative >= fastest_opponent {
+                    self.outcome = Some(BattleOutcome::Fled);
+                    return;
+                }
+            }
+        }
+        if self.side_defeated(Faction::Enemy) {
+            self.outcome = Some(BattleOutcome::Victory);
+        } else if self.side_defeated(Faction::Player) {
+            self.outcome = Some(BattleOutcome::Defeat);
+        }
+        let combatants = &self.combatants;
+        self.turn_queue.retain(|&i| combatants[i].is_alive());
+        if self.turn_queue.is_empty() {
+            self.fill_turn_queue();
+        }
+    }
+
+    /// The command an enemy takes on its turn: attack the first standing opponent.
+    pub fn enemy_command(&self, actor: usize) -> BattleCommand {
+        let faction = self.combatants[actor].faction;
+        let target = self
+            .combatants
+            .iter()
+            .position(|c| c.faction != faction && c.is_alive())
+            .unwrap_or(actor);
+        BattleCommand::Attack { target }
+    }
+
+    fn first_living_enemy(&self) -> Option<usize> {
+        self.combatants
+            .iter()
+            .position(|c| c.faction == Faction::Enemy && c.is_alive())
+    }
+}
+
+/// Where the player was in the overworld when the battle started.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+struct OverworldReturn(Transform);
+
+#[derive(Component)]
+struct BattleScreen;
+
+impl Plugin for BattlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EncounterEvent>()
+            .add_event::<BattleCommandEvent>()
+            .add_event::<BattleFinished>()
+            .add_systems(Update, encounter_check.run_if(is_playing))
+            .add_systems(Update, start_battle.run_if(is_playing))
+            .add_systems(
+                Update,
+                (
+                    battle_input,
+                    enemy_turns,
+                    battle_turns,
+                    finish_battle,
+                    battle_screen_text,
+                )
+                    .chain()
+                    .before(apply_damage)
+                    .run_if(in_state(GameState::Battle)),
+            )
+            .add_systems(OnEnter(GameState::Battle), spawn_battle_screen)
+            .add_systems(
+                OnExit(GameState::Battle),
+                (return_to_overworld, despawn_battle_screen),
+            );
+    }
+}
+
+fn encounter_check(
+    mut collision_events: EventReader<CollisionEvent>,
+    player_query: Query<(), With<Player>>,
+    encounter_query: Query<&Encounter>,
+    mut ev_encounter: EventWriter<EncounterEvent>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(l, r, _) = event else {
+            continue;
+        };
+        let source = if player_query.contains(*l) {
+            *r
+        } else if player_query.contains(*r) {
+            *l
+        } else {
+            continue;
+        };
+        if let Ok(encounter) = encounter_query.get(source) {
+            ev_encounter.send(EncounterEvent {
+                group: encounter.0.clone(),
+                source: Some(source),
+            });
+            return;
+        }
+    }
+}
+
+type BattlePlayer<'a> = (
+    &'a Transform,
+    &'a Health,
+    &'a DerivedStats,
+    &'a Attributes,
+    Option<&'a Inventory>,
+);
+
+fn battle_items(inventory: &Inventory, database: &ItemDatabase) -> Vec<BattleItem> {
+    let mut items: Vec<BattleItem> = Vec::new();
+    for stack in inventory.slots().iter().flatten() {
+        if items.iter().any(|item| item.item == stack.item) {
+            continue;
+        }
+        let effect = database.get(stack.item).and_then(|item| item.use_effect);
+        if let Some(UseEffect::Heal(heal)) = effect {
+            items.push(BattleItem::new(
+                stack.item,
+                heal,
+                inventory.count(stack.item),
+            ));
+        }
+    }
+    items
+}
+
+fn start_battle(
+    mut commands: Commands,
+    mut ev_encounter: EventReader<EncounterEvent>,
+    database: Option<Res<ItemDatabase>>,
+    player_query: Query<BattlePlayer, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(encounter) = ev_encounter.read().next() else {
+        return;
+    };
+    let Ok((transform, health, stats, attributes, inventory)) = player_query.get_single() else {
+        return;
+    };
+    let mut combatants = vec![Combatant {
+        name: "Player".to_string(),
+        faction: Faction::Player,
+        stats: *stats,
+        initiative: attributes.dexterity,
+        intelligence: attributes.intelligence,
+        health: *health,
+        experience: 0,
+    }];
+    combatants.extend(encounter.group.0.iter().map(|enemy| {
+        let stats = DerivedStats::compute(&enemy.attributes, &StatModifiers::default());
+        Combatant {
+            name: enemy.name.clone(),
+            faction: Faction::Enemy,
+            stats,
+            initiative: enemy.attributes.dexterity,
+            intelligence: enemy.attributes.intelligence,
+            health: Health::new(stats.max_health),
+            experience: enemy.experience,
+        }
+    }));
+    let items = match (inventory, database) {
+        (Some(inventory), Some(database)) => battle_items(inventory, &database),
+        _ => Vec::new(),
+    };
+    let mut battle = Battle::new(combatants).with_items(items);
+    battle.source = encounter.source;
+    commands.insert_resource(battle);
+    commands.insert_resource(OverworldReturn(*transform));
+    next_state.set(GameState::Battle);
+}
+
+fn battle_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    battle: Res<Battle>,
+    mut ev_command: EventWriter<BattleCommandEvent>,
+) {
+    let Some(actor) = battle.current() else {
+        return;
+    };
+    if battle.combatants[actor].faction != Faction::Player {
+        return;
+    }
+    let target = battle.first_living_enemy().unwrap_or(actor);
+    let command = if keyboard_input.just_pressed(KeyCode::Digit1) {
+        BattleCommand::Attack { target }
+    } else if keyboard_input.just_pressed(KeyCode::Digit2) {
+        BattleCommand::Skill { target, power: 1.5 }
+    } else if keyboard_input.just_pressed(KeyCode::Digit3) {
+        let Some(item) = battle.usable_item() else {
+            return;
+        };
+        BattleCommand::Item { item: item.item }
+    } else if keyboard_input.just_pressed(KeyCode::Digit4) {
+        BattleCommand::Flee
+    } else {
+        return;
+    };
+    ev_command.send(BattleCommandEvent(command));
+}
+
+fn enemy_turns(battle: Res<Battle>, mut ev_command: EventWriter<BattleCommandEvent>) {
+    if let Some(actor) = battle.current() {
+        if battle.combatants[actor].faction == Faction::Enemy {
+            ev_command.send(BattleCommandEvent(battle.enemy_command(actor)));
+        }
+    }
+}
+
+fn battle_turns(mut battle: ResMut<Battle>, mut ev_command: EventReader<BattleCommandEvent>) {
+    for command in ev_command.read() {
+        battle.execute(command.0);
+    }
+}
+
+fn finish_battle(
+    battle: Res<Battle>,
+    mut player_query: Query<(Entity, &mut Health, Option<&mut Inventory>), With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_finished: EventWriter<BattleFinished>,
+    mut ev_experience: EventWriter<ExperienceGained>,
+    mut ev_defeated: EventWriter<Defeated>,
+) {
+    let Some(outcome) = battle.outcome() else {
+        return;
+    };
+    if let Ok((player, mut health, inventory)) = player_query.get_single_mut() {
+        if let Some(mut inventory) = inventory {
+            for item in battle.items.iter().filter(|item| item.used > 0) {
+                if let Err(err) = inventory.remove(item.item, item.used) {
+                    warn!("Couldn't take item {} used in battle: {err}", item.item.0);
+                }
+            }
+        }
+        if let Some(combatant) = battle
+            .combatants
+            .iter()
+            .find(|c| c.faction == Faction::Player)
+        {
+            *health = combatant.health;
+        }
+        match outcome {
+            BattleOutcome::Victory => {
+                let amount = battle.combatants.iter().map(|c| c.experience).sum();
+                ev_experience.send(ExperienceGained {
+                    entity: player,
+                    amount,
+                });
+                if let Some(source) = battle.source {
+                    ev_defeated.send(Defeated { entity: source });
+                }
+            }
+            BattleOutcome::Defeat => {
+                ev_defeated.send(Defeated { entity: player });
+            }
+            BattleOutcome::Fled => {}
+        }
+    }
+    ev_finished.send(BattleFinished(outcome));
+    next_state.set(GameState::Playing);
+}
+
+fn return_to_overworld(
+    mut commands: Commands,
+    battle: Res<Battle>,
+    overworld_return: Res<OverworldReturn>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+) {
+    if let Ok(mut transform) = player_query.get_single_mut() {
+        *transform = overworld_return.0;
+    }
+    if battle.outcome() == Some(BattleOutcome::Victory) {
+        if let Some(source) = battle.source {
+            if let Some(entity) = commands.get_entity(source) {
+                entity.despawn_recursive();
+            }
+        }
+    }
+    commands.remove_resource::<OverworldReturn>();
+    commands.remove_resource::<Battle>();
+}
+
+fn spawn_battle_screen(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(24.)),
+                ..default()
+            },
+            background_color: Color::srgb(0.05, 0.05, 0.15).into(),
+            ..default()
+        })
+        .insert(BattleScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 24.,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn battle_screen_text(
+    battle: Res<Battle>,
+    screen_query: Query<&Children, With<BattleScreen>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !battle.is_changed() {
+        return;
+    }
+    let mut lines: Vec<String> = battle
+        .combatants
+        .iter()
+        .map(|c| format!("{}: {:.0}/{:.0}", c.name, c.health.current, c.health.max))
+        .collect();
+    lines.push(String::new());
+    let items = battle.usable_item().map_or(0, |item| item.count);
+    lines.push(format!("1: Attack  2: Skill  3: Item ({items})  4: Flee"));
+    for children in screen_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = lines.join("\n");
+            }
+        }
+    }
+}
+
+fn despawn_battle_screen(mut commands: Commands, screen_query: Query<Entity, With<BattleScreen>>) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Battle, BattleCommand, BattleEnemy, BattleFinished, BattleItem, BattleOutcome, Combatant,
+        EncounterEvent, EnemyGroup, OverworldReturn,
+    };
+    use crate::combat::{Defeated, Faction, Health};
+    use crate::inventory::Inventory;
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition, ItemId, UseEffect};
+    use crate::player::{self, Player};
+    use crate::progression::ExperienceGained;
+    use crate::state::{GameState, GameStatePlugin};
+    use crate::stats::{Attributes, DerivedStats, StatModifiers};
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::plugin::{NoUserData, RapierConfiguration, RapierPhysicsPlugin};
+
+    fn combatant(name: &str, faction: Faction, initiative: f32, health: f32) -> Combatant {
+        Combatant {
+            name: name.to_string(),
+            faction,
+            stats: DerivedStats::compute(&Attributes::default(), &StatModifiers::default()),
+            initiative,
+            intelligence: 10.,
+            health: Health::new(health),
+            experience: 5,
+        }
+    }
+
+    #[test]
+    fn turns_follow_initiative() {
+        let mut battle = Battle::new(vec![
+            combatant("Player", Faction::Player, 10., 100.),
+            combatant("Slow", Faction::Enemy, 5., 100.),
+            combatant("Fast", Faction::Enemy, 15., 100.),
+        ]);
+        assert_eq!(battle.current(), Some(2));
+        battle.execute(battle.enemy_command(2));
+        assert_eq!(battle.current(), Some(0));
+        battle.execute(BattleCommand::Flee);
+        assert_eq!(battle.current(), Some(1));
+        battle.execute(battle.enemy_command(1));
+        assert_eq!(battle.current(), Some(2));
+        assert!(battle.combatants[0].health.current < 100.);
+    }
+
+    #[test]
+    fn defeating_all_enemies_wins() {
+        let mut battle = Battle::new(vec![
+            combatant("Player", Faction::Player, 10., 100.),
+            combatant("Weak", Faction::Enemy, 5., 1.),
+        ]);
+        battle.execute(BattleCommand::Attack { target: 1 });
+        assert_eq!(battle.outcome(), Some(BattleOutcome::Victory));
+
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.insert_state(GameState::Battle);
+        app.add_event::<Defeated>();
+        app.add_event::<ExperienceGained>();
+        app.add_event::<BattleFinished>();
+        app.add_systems(Update, super::finish_battle);
+        app.world_mut()
+            .spawn(Player::default())
+            .insert(Health::new(100.));
+        let source = app.world_mut().spawn_empty().id();
+        battle.source = Some(source);
+        app.insert_resource(battle);
+        app.update();
+        let events = app.world().resource::<Events<Defeated>>();
+        let defeated: Vec<_> = events.get_reader().read(events).copied().collect();
+        assert_eq!(defeated, vec![Defeated { entity: source }]);
+    }
+
+    #[test]
+    fn items_are_used_up() {
+        let potion = ItemDefinition {
+            category: ItemCategory::Consumable,
+            use_effect: Some(UseEffect::Heal(30.)),
+            ..material(ItemId(0), "Potion")
+        };
+        let database = ItemDatabase::new(vec![potion, material(ItemId(1), "Herb")]);
+        let mut inventory = Inventory::new(3);
+        inventory.add(&database, ItemId(1), 2).unwrap();
+        inventory.add(&database, ItemId(0), 1).unwrap();
+        let items = super::battle_items(&inventory, &database);
+        assert_eq!(items, vec![BattleItem::new(ItemId(0), 30., 1)]);
+
+        let mut battle = Battle::new(vec![
+            combatant("Player", Faction::Player, 10., 100.),
+            combatant("Slime", Faction::Enemy, 5., 100.),
+        ])
+        .with_items(items);
+        battle.combatants[0].health.current = 50.;
+        battle.execute(BattleCommand::Item { item: ItemId(0) });
+        assert_eq!(battle.combatants[0].health.current, 80.);
+        assert_eq!(battle.current(), Some(1));
+        battle.execute(battle.enemy_command(1));
+        let health = battle.combatants[0].health.current;
+        battle.execute(BattleCommand::Item { item: ItemId(0) });
+        battle.execute(BattleCommand::Item { item: ItemId(1) });
+        assert_eq!(battle.combatants[0].health.current, health);
+        assert_eq!(battle.current(), Some(0));
+        assert_eq!(battle.usable_item(), None);
+
+        battle.execute(BattleCommand::Flee);
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.insert_state(GameState::Battle);
+        app.add_event::<Defeated>();
+        app.add_event::<ExperienceGained>();
+        app.add_event::<BattleFinished>();
+        app.add_systems(Update, super::finish_battle);
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(Health::new(100.))
+            .insert(inventory)
+            .id();
+        app.insert_resource(battle);
+        app.update();
+        let inventory = app.world().get::<Inventory>(player).unwrap();
+        assert_eq!(inventory.count(ItemId(0)), 0);
+        assert_eq!(inventory.count(ItemId(1)), 2);
+    }
+
+    #[test]
+    fn fleeing_requires_initiative() {
+        let mut battle = Battle::new(vec![
+            combatant("Player", Faction::Player, 10., 100.),
+            combatant("Fast", Faction::Enemy, 5., 100.),
+            combatant("Faster", Faction::Enemy, 12., 100.),
+        ]);
+        battle.execute(battle.enemy_command(2));
+        battle.execute(BattleCommand::Flee);
+        assert_eq!(battle.outcome(), None);
+
+        let mut battle = Battle::new(vec![
+            combatant("Player", Faction::Player, 10., 100.),
+            combatant("Slow", Faction::Enemy, 5., 100.),
+        ]);
+        battle.execute(BattleCommand::Flee);
+        assert_eq!(battle.outcome(), Some(BattleOutcome::Fled));
+    }
+
+    #[test]
+    fn battle_returns_to_overworld_position() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(bevy::input::InputPlugin);
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        app.add_plugins(bevy::asset::AssetPlugin::default());
+        app.add_plugins(player::test_utils::PlayerPlugin);
+        app.insert_state(GameState::Playing);
+        app.add_plugins(GameStatePlugin);
+        app.world_mut()
+            .resource_mut::<RapierConfiguration>()
+            .gravity = Vec2::ZERO;
+        app.add_event::<crate::combat::Defeated>();
+        app.add_event::<crate::progression::ExperienceGained>();
+        app.add_plugins(super::BattlePlugin);
+        app.update();
+
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(app.world());
+        app.world_mut()
+            .entity_mut(player)
+            .insert(Transform::from_xyz(25., -10., 0.));
+        app.world_mut().send_event(EncounterEvent {
+            group: EnemyGroup(vec![BattleEnemy {
+                name: "Slime".to_string(),
+                attributes: Attributes::default(),
+                experience: 10,
+            }]),
+            source: None,
+        });
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Battle
+        );
+        assert_eq!(
+            app.world().resource::<OverworldReturn>().0,
+            Transform::from_xyz(25., -10., 0.)
+        );
+
+        app.world_mut()
+            .entity_mut(player)
+            .insert(Transform::from_xyz(0., 0., 0.));
+        app.world_mut()
+            .resource_mut::<Battle>()
+            .execute(BattleCommand::Flee);
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Playing
+        );
+        assert_eq!(
+            app.world().get::<Transform>(player),
+            Some(&Transform::from_xyz(25., -10., 0.))
+        );
+        assert!(app.world().get_resource::<Battle>().is_none());
+    }
+}
@@ -0,0 +1,65 @@
+//! Prints the passage graph of an area data file as Graphviz DOT or JSON. Fails if a passage
+//! leads to an area that doesn't exist.
+//!
+//! Usage: `area_graph <areas.ron> [dot|json]`
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use rpg_system_2d::area::GameAreas;
+use rpg_system_2d::graph::AreaGraph;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (path, format) = match args.as_slice() {
+        [path] => (path, "dot"),
+        [path, format] if format == "dot" || format == "json" => (path, format.as_str()),
+        _ => {
+            eprintln!("usage: area_graph <areas.ron> [dot|json]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("couldn't read {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let game_areas = match GameAreas::from_ron(&source) {
+        Ok(game_areas) => game_areas,
+        Err(error) => {
+            eprintln!("couldn't parse {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let graph = AreaGraph::new(&game_areas);
+    for node in graph.dead_ends() {
+        eprintln!("dead end: area {}", node.area.0);
+    }
+    for link in graph.one_way_links() {
+        eprintln!("one-way passage: area {} -> {}", link.from.0, link.to.0);
+    }
+    for link in graph.dangling_links() {
+        eprintln!(
+            "invalid passage: area {} -> {}, which doesn't exist",
+            link.from.0, link.to.0
+        );
+    }
+    if format == "json" {
+        match graph.to_json() {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("couldn't serialize graph: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print!("{}", graph.to_dot());
+    }
+    if graph.dangling_links().next().is_some() {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
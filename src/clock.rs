@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::state::is_playing;
+
+pub struct ClockPlugin;
+
+/// In-game time of day, which only advances while playing.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Resource, Serialize)]
+pub struct GameClock {
+    pub day: u32,
+    /// Hours since midnight, in `0..24`.
+    pub hour: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock { day: 0, hour: 8. }
+    }
+}
+
+impl GameClock {
+    pub fn advance(&mut self, hours: f32) {
+        let hour = self.hour + hours;
+        self.day += (hour / 24.).floor() as u32;
+        self.hour = hour.rem_euclid(24.);
+    }
+}
+
+#[derive(Clone, Debug, Resource)]
+pub struct ClockSettings {
+    /// In-game hours that pass per real second.
+    pub hours_per_second: f32,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        // A day lasts 24 real minutes.
+        ClockSettings {
+            hours_per_second: 1. / 60.,
+        }
+    }
+}
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .init_resource::<ClockSettings>()
+            .add_systems(Update, tick_clock.run_if(is_playing));
+    }
+}
+
+fn tick_clock(time: Res<Time>, settings: Res<ClockSettings>, mut clock: ResMut<GameClock>) {
+    clock.advance(time.delta_seconds() * settings.hours_per_second);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameClock;
+
+    #[test]
+    fn clock_wraps_into_next_day() {
+        let mut clock = GameClock::default();
+        clock.advance(10.);
+        assert_eq!(clock, GameClock { day: 0, hour: 18. });
+        clock.advance(30.);
+        assert_eq!(clock, GameClock { day: 2, hour: 0. });
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::enemy::Enemy;
+use crate::player::{InputLockout, Player};
+use crate::stats::DerivedStats;
+
+pub struct CombatPlugin;
+
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.
+    }
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// Sent when an entity's health runs out. Defeated enemies are despawned in `PostUpdate`, so
+/// their components can still be read by systems handling this event during `Update`.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct Defeated {
+    pub entity: Entity,
+}
+
+/// Damages and knocks back the player on touch. Each enemy type configures its own values.
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub struct ContactDamage {
+    pub damage: f32,
+    pub knockback: f32,
+    /// Seconds during which the player's movement input is ignored after being knocked back.
+    pub lockout: f32,
+}
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<Defeated>()
+            .add_systems(Update, enable_contact_events)
+            .add_systems(Update, contact_damage.before(apply_damage))
+            .add_systems(Update, apply_damage)
+            .add_systems(PostUpdate, despawn_defeated_enemies);
+    }
+}
+
+pub(crate) fn apply_damage(
+    mut ev_damage: EventReader<DamageEvent>,
+    mut health_query: Query<(&mut Health, Option<&DerivedStats>)>,
+    mut ev_defeated: EventWriter<Defeated>,
+) {
+    for damage in ev_damage.read() {
+        let Ok((mut health, stats)) = health_query.get_mut(damage.target) else {
+            continue;
+        };
+        if health.is_depleted() {
+            continue;
+        }
+        let defense = stats.map_or(0., |stats| stats.defense);
+        let amount = damage.amount * 100. / (100. + defense);
+        health.current = (health.current - amount).max(0.);
+        if health.is_depleted() {
+            ev_defeated.send(Defeated {
+                entity: damage.target,
+            });
+        }
+    }
+}
+
+fn enable_contact_events(
+    mut commands: Commands,
+    contact_query: Query<Entity, Added<ContactDamage>>,
+) {
+    for entity in contact_query.iter() {
+        commands
+            .entity(entity)
+            .insert(ActiveEvents::COLLISION_EVENTS);
+    }
+}
+
+fn contact_damage(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut player_query: Query<(&Transform, &mut Velocity), With<Player>>,
+    contact_query: Query<(&ContactDamage, &Transform)>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(l, r, _) = event else {
+            continue;
+        };
+        let (player, other) = if player_query.contains(*l) {
+            (*l, *r)
+        } else if player_query.contains(*r) {
+            (*r, *l)
+        } else {
+            continue;
+        };
+        let Ok((contact, contact_transform)) = contact_query.get(other) else {
+            continue;
+        };
+        let (player_transform, mut velocity) = player_query.get_mut(player).unwrap();
+        ev_damage.send(DamageEvent {
+            target: player,
+            amount: contact.damage,
+        });
+        let direction = (player_transform.translation - contact_transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        velocity.linvel = direction * contact.knockback;
+        commands
+            .entity(player)
+            .insert(InputLockout::new(contact.lockout));
+    }
+}
+
+fn despawn_defeated_enemies(
+    mut commands: Commands,
+    mut ev_defeated: EventReader<Defeated>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    for defeated in ev_defeated.read() {
+        if enemy_query.contains(defeated.entity) {
+            commands.entity(defeated.entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContactDamage, DamageEvent};
+    use crate::player::{self, InputLockout, Player};
+    use bevy::prelude::*;
+    use bevy_rapier2d::prelude::*;
+
+    fn get_test_app() -> App {
+        let mut ret = App::default();
+        ret.add_plugins(MinimalPlugins);
+        ret.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        ret.add_plugins(bevy::asset::AssetPlugin::default());
+        ret.add_event::<DamageEvent>();
+        ret
+    }
+
+    #[test]
+    fn contact_damages_and_knocks_back_player() {
+        let mut app = get_test_app();
+        app.add_plugins(player::test_utils::PlayerPlugin);
+        app.world_mut()
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                40., 0., 0.,
+            )))
+            .insert(Collider::cuboid(30., 30.))
+            .insert(RigidBody::Fixed)
+            .insert(ContactDamage {
+                damage: 7.,
+                knockback: 100.,
+                lockout: 0.5,
+            });
+        app.add_systems(Update, super::enable_contact_events);
+        app.add_systems(Last, super::contact_damage);
+        app.update();
+        app.update();
+
+        let events = app.world().resource::<Events<DamageEvent>>();
+        let damage: Vec<_> = events.get_reader().read(events).copied().collect();
+        assert_eq!(damage.len(), 1);
+        assert_eq!(damage[0].amount, 7.);
+
+        let mut player_query = app
+            .world_mut()
+            .query_filtered::<(Entity, &Velocity, Has<InputLockout>), With<Player>>();
+        let (player, velocity, locked_out) = player_query.single(app.world());
+        assert_eq!(damage[0].target, player);
+        assert!(velocity.linvel.x < 0.);
+        assert!(locked_out);
+    }
+}
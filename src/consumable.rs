@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::area::{AreaTransitionEvent, PassageDestination};
+use crate::combat::Health;
+use crate::inventory::Inventory;
+use crate::item::{ItemDatabase, ItemId, UseEffect};
+use crate::player::Player;
+use crate::state::is_playing;
+use crate::stats::{
+    ModifierKind, ModifierSource, Stat, StatModifier, StatModifiers, TimedModifiers,
+};
+
+pub struct ConsumablePlugin;
+
+/// Where a return scroll takes its user. The player's is where it spawned in the first area.
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub struct ReturnPoint(pub PassageDestination);
+
+/// Inventory slot whose item is used with Q, counted like the HUD's quick slots from 0.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct QuickUseSlot(pub usize);
+
+#[derive(Clone, Component, Debug, Default)]
+pub struct ItemCooldowns(HashMap<ItemId, Timer>);
+
+impl ItemCooldowns {
+    pub fn is_ready(&self, item: ItemId) -> bool {
+        self.0.get(&item).is_none_or(Timer::finished)
+    }
+}
+
+/// Asks for an item in the entity's inventory to be used.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct UseItem {
+    pub entity: Entity,
+    pub item: ItemId,
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct ItemUsed {
+    pub entity: Entity,
+    pub item: ItemId,
+}
+
+impl Plugin for ConsumablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuickUseSlot>()
+            .add_event::<UseItem>()
+            .add_event::<ItemUsed>()
+            .add_systems(Update, quick_use.run_if(is_playing))
+            .add_systems(Update, (tick_item_cooldowns, use_items).chain());
+    }
+}
+
+fn quick_use(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    quick_slot: Res<QuickUseSlot>,
+    database: Res<ItemDatabase>,
+    player_query: Query<(Entity, &Inventory), With<Player>>,
+    mut ev_use_item: EventWriter<UseItem>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyQ) {
+        return;
+    }
+    let Ok((entity, inventory)) = player_query.get_single() else {
+        return;
+    };
+    let usable = inventory
+        .slots()
+        .get(quick_slot.0)
+        .copied()
+        .flatten()
+        .filter(|stack| {
+            database
+                .get(stack.item)
+                .is_some_and(|definition| definition.use_effect.is_some())
+        });
+    if let Some(stack) = usable {
+        ev_use_item.send(UseItem {
+            entity,
+            item: stack.item,
+        });
+    }
+}
+
+fn tick_item_cooldowns(time: Res<Time>, mut cooldown_query: Query<&mut ItemCooldowns>) {
+    for mut cooldowns in cooldown_query.iter_mut() {
+        for timer in cooldowns.0.values_mut() {
+            timer.tick(time.delta());
+        }
+    }
+}
+
+type UserComponents<'a> = (
+    &'a mut Inventory,
+    &'a mut ItemCooldowns,
+    Option<&'a mut Health>,
+    Option<&'a mut StatModifiers>,
+    Option<&'a mut TimedModifiers>,
+    Option<&'a ReturnPoint>,
+);
+
+fn use_items(
+    mut ev_use_item: EventReader<UseItem>,
+    database: Res<ItemDatabase>,
+    mut user_query: Query<UserComponents>,
+    mut ev_area_transition: EventWriter<AreaTransitionEvent>,
+    mut ev_item_used: EventWriter<ItemUsed>,
+) {
+    for request in ev_use_item.read() {
+        let Ok((mut inventory, mut cooldowns, health, modifiers, timed, return_point)) =
+            user_query.get_mut(request.entity)
+        else {
+            continue;
+        };
+        let Some(definition) = database.get(request.item) else {
+            continue;
+        };
+        let Some(effect) = definition.use_effect else {
+            continue;
+        };
+        if inventory.count(request.item) == 0 || !cooldowns.is_ready(request.item) {
+            continue;
+        }
+        match effect {
+            UseEffect::Heal(amount) => {
+                let Some(mut health) = health.filter(|health| health.current < health.max) else {
+                    continue;
+                };
+                health.current = (health.current + amount).min(health.max);
+            }
+            UseEffect::SpeedBoost { percent, duration } => {
+                let (Some(mut modifiers), Some(mut timed)) = (modifiers, timed) else {
+                    continue;
+                };
+                timed.apply(
+                    &mut modifiers,
+                    StatModifier {
+                        source: ModifierSource::Buff(request.item.0),
+                        stat: Stat::MoveSpeed,
+                        kind: ModifierKind::Percent(percent),
+                    },
+                    duration,
+                );
+            }
+            UseEffect::ReturnScroll => {
+                let Some(return_point) = return_point else {
+                    continue;
+                };
+                ev_area_transition.send(AreaTransitionEvent(return_point.0));
+            }
+        }
+        inventory.remove(request.item, 1).unwrap();
+        cooldowns.0.insert(
+            request.item,
+            Timer::from_seconds(definition.use_cooldown, TimerMode::Once),
+        );
+        ev_item_used.send(ItemUsed {
+            entity: request.entity,
+            item: request.item,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsumablePlugin, ItemCooldowns, ItemUsed, QuickUseSlot, ReturnPoint, UseItem};
+    use crate::area::{AreaTransitionEvent, PassageDestination};
+    use crate::combat::Health;
+    use crate::inventory::Inventory;
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition, ItemId, UseEffect};
+    use crate::player::Player;
+    use crate::stats::{
+        Attributes, DerivedStats, StatModifiers, StatsBundle, StatsPlugin, TimedModifiers,
+    };
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const POTION: ItemId = ItemId(0);
+    const BOOST: ItemId = ItemId(1);
+    const SCROLL: ItemId = ItemId(2);
+
+    fn get_test_app() -> (App, Entity) {
+        let consumable = |id, effect| ItemDefinition {
+            category: ItemCategory::Consumable,
+            use_effect: Some(effect),
+            use_cooldown: 1.,
+            ..material(id, &format!("{effect:?}"))
+        };
+        let database = ItemDatabase::new(vec![
+            consumable(POTION, UseEffect::Heal(30.)),
+            consumable(
+                BOOST,
+                UseEffect::SpeedBoost {
+                    percent: 0.5,
+                    duration: 2.,
+                },
+            ),
+            consumable(SCROLL, UseEffect::ReturnScroll),
+        ]);
+        let mut inventory = Inventory::new(4);
+        inventory.add(&database, POTION, 2).unwrap();
+        inventory.add(&database, BOOST, 1).unwrap();
+        inventory.add(&database, SCROLL, 1).unwrap();
+
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+        app.insert_resource(database);
+        app.add_event::<AreaTransitionEvent>();
+        app.add_plugins(ConsumablePlugin);
+        app.add_plugins(StatsPlugin);
+        let user = app
+            .world_mut()
+            .spawn(StatsBundle::new(Attributes::default()))
+            .insert(Health {
+                current: 10.,
+                max: 100.,
+            })
+            .insert(inventory)
+            .insert(ItemCooldowns::default())
+            .insert(TimedModifiers::default())
+            .insert(ReturnPoint(PassageDestination(
+                2.into(),
+                Transform::from_xyz(5., 5., 0.),
+            )))
+            .id();
+        (app, user)
+    }
+
+    fn use_item(app: &mut App, entity: Entity, item: ItemId) -> usize {
+        app.world_mut().send_event(UseItem { entity, item });
+        app.update();
+        let events = app.world().resource::<Events<ItemUsed>>();
+        events
+            .get_reader()
+            .read(events)
+            .filter(|used| used.item == item)
+            .count()
+    }
+
+    #[test]
+    fn heal_consumes_item_and_respects_cooldown() {
+        let (mut app, user) = get_test_app();
+        // Healing isn't wasted at full health.
+        app.world_mut().get_mut::<Health>(user).unwrap().current = 100.;
+        assert_eq!(use_item(&mut app, user, POTION), 0);
+        assert_eq!(app.world().get::<Inventory>(user).unwrap().count(POTION), 2);
+        app.world_mut().get_mut::<Health>(user).unwrap().current = 10.;
+
+        assert_eq!(use_item(&mut app, user, POTION), 1);
+        assert_eq!(app.world().get::<Health>(user).unwrap().current, 40.);
+        app.update();
+        app.update();
+        assert_eq!(use_item(&mut app, user, POTION), 0);
+        assert_eq!(app.world().get::<Health>(user).unwrap().current, 40.);
+        assert_eq!(app.world().get::<Inventory>(user).unwrap().count(POTION), 1);
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(use_item(&mut app, user, POTION), 1);
+        assert_eq!(app.world().get::<Health>(user).unwrap().current, 70.);
+    }
+
+    #[test]
+    fn quick_use_uses_the_configured_slot() {
+        let (mut app, user) = get_test_app();
+        app.world_mut().entity_mut(user).insert(Player::default());
+        let quick_use = |app: &mut App, slot| {
+            app.insert_resource(QuickUseSlot(slot));
+            let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            input.release_all();
+            input.clear();
+            input.press(KeyCode::KeyQ);
+            app.update();
+            let inventory = app.world().get::<Inventory>(user).unwrap();
+            (inventory.count(POTION), inventory.count(SCROLL))
+        };
+        assert_eq!(quick_use(&mut app, 0), (1, 1));
+        assert_eq!(quick_use(&mut app, 2), (1, 0));
+        assert_eq!(quick_use(&mut app, 3), (1, 0));
+    }
+
+    #[test]
+    fn speed_boost_expires() {
+        let (mut app, user) = get_test_app();
+        app.update();
+        let base_speed = app.world().get::<DerivedStats>(user).unwrap().move_speed;
+        assert_eq!(use_item(&mut app, user, BOOST), 1);
+        app.update();
+        assert_eq!(
+            app.world().get::<DerivedStats>(user).unwrap().move_speed,
+            base_speed * 1.5
+        );
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().get::<DerivedStats>(user).unwrap().move_speed,
+            base_speed
+        );
+        assert_eq!(
+            app.world().get::<StatModifiers>(user).unwrap(),
+            &StatModifiers::default()
+        );
+    }
+
+    #[test]
+    fn return_scroll_transitions_to_return_point() {
+        let (mut app, user) = get_test_app();
+        assert_eq!(use_item(&mut app, user, SCROLL), 1);
+        let events = app.world().resource::<Events<AreaTransitionEvent>>();
+        let transitions: Vec<_> = events.get_reader().read(events).copied().collect();
+        assert_eq!(
+            transitions,
+            vec![AreaTransitionEvent(PassageDestination(
+                2.into(),
+                Transform::from_xyz(5., 5., 0.)
+            ))]
+        );
+    }
+}
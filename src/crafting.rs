@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::area::{AreaIdentifier, CurrentArea};
+use crate::inventory::{Inventory, InventoryChanged, InventoryError, ItemStack};
+use crate::item::ItemDatabase;
+
+pub struct CraftingPlugin;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct RecipeId(pub usize);
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Recipe {
+    pub id: RecipeId,
+    pub name: String,
+    pub inputs: Vec<ItemStack>,
+    pub outputs: Vec<ItemStack>,
+    /// Kind of crafting station that has to be in the current area.
+    pub station: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Resource)]
+pub struct RecipeBook {
+    recipes: HashMap<RecipeId, Recipe>,
+}
+
+impl RecipeBook {
+    pub fn new(recipes: Vec<Recipe>) -> Self {
+        RecipeBook {
+            recipes: recipes
+                .into_iter()
+                .map(|recipe| (recipe.id, recipe))
+                .collect(),
+        }
+    }
+
+    /// Parses a list of recipes written in RON, with the same extensions as
+    /// [`DialogueTree::from_ron`](crate::dialogue::DialogueTree::from_ron).
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .map(RecipeBook::new)
+    }
+
+    pub fn get(&self, id: RecipeId) -> Option<&Recipe> {
+        self.recipes.get(&id)
+    }
+}
+
+/// Recipes an entity has discovered and can craft.
+#[derive(Clone, Component, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KnownRecipes(pub HashSet<RecipeId>);
+
+#[derive(Clone, Component, Debug, Eq, PartialEq)]
+pub struct CraftingStation(pub String);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CraftingError {
+    UnknownRecipe(RecipeId),
+    NotDiscovered(RecipeId),
+    MissingStation(String),
+    Inventory(InventoryError),
+}
+
+impl fmt::Display for CraftingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CraftingError::UnknownRecipe(recipe) => write!(f, "unknown recipe {}", recipe.0),
+            CraftingError::NotDiscovered(recipe) => {
+                write!(f, "recipe {} hasn't been discovered", recipe.0)
+            }
+            CraftingError::MissingStation(station) => write!(f, "requires a {station}"),
+            CraftingError::Inventory(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CraftingError {}
+
+impl From<InventoryError> for CraftingError {
+    fn from(error: InventoryError) -> Self {
+        CraftingError::Inventory(error)
+    }
+}
+
+/// Crafts a recipe, replacing its inputs with its outputs. The inventory is left untouched if
+/// crafting fails.
+pub fn craft(
+    recipe: &Recipe,
+    known: &KnownRecipes,
+    inventory: &mut Inventory,
+    database: &ItemDatabase,
+    stations: &[&str],
+) -> Result<(), CraftingError> {
+    if !known.0.contains(&recipe.id) {
+        return Err(CraftingError::NotDiscovered(recipe.id));
+    }
+    if let Some(station) = &recipe.station {
+        if !stations.contains(&station.as_str()) {
+            return Err(CraftingError::MissingStation(station.clone()));
+        }
+    }
+    let mut crafted = inventory.clone();
+    for input in &recipe.inputs {
+        crafted.remove(input.item, input.count)?;
+    }
+    for output in &recipe.outputs {
+        crafted.add(database, output.item, output.count)?;
+    }
+    *inventory = crafted;
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct Craft {
+    pub entity: Entity,
+    pub recipe: RecipeId,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct CraftResult {
+    pub entity: Entity,
+    pub recipe: RecipeId,
+    pub result: Result<(), CraftingError>,
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct RecipeDiscovered {
+    pub entity: Entity,
+    pub recipe: RecipeId,
+}
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeBook>()
+            .add_event::<Craft>()
+            .add_event::<CraftResult>()
+            .add_event::<RecipeDiscovered>()
+            .add_systems(Update, craft_requests)
+            .add_systems(Update, discover_recipes);
+    }
+}
+
+fn craft_requests(
+    mut ev_craft: EventReader<Craft>,
+    recipes: Res<RecipeBook>,
+    database: Res<ItemDatabase>,
+    current_area: Option<Res<CurrentArea>>,
+    station_query: Query<(&CraftingStation, Option<&AreaIdentifier>)>,
+    mut crafter_query: Query<(&KnownRecipes, &mut Inventory)>,
+    mut ev_result: EventWriter<CraftResult>,
+) {
+    let stations: Vec<&str> = station_query
+        .iter()
+        .filter(|(_, area)| match (area, &current_area) {
+            (Some(area), Some(current_area)) => **area == current_area.0,
+            _ => true,
+        })
+        .map(|(station, _)| station.0.as_str())
+        .collect();
+    for request in ev_craft.read() {
+        let Ok((known, mut inventory)) = crafter_query.get_mut(request.entity) else {
+            continue;
+        };
+        // Failed crafts leave the inventory unchanged, so they mustn't mark it as changed either.
+        let mut crafted = inventory.clone();
+        let result = match recipes.get(request.recipe) {
+            Some(recipe) => craft(recipe, known, &mut crafted, &database, &stations),
+            None => Err(CraftingError::UnknownRecipe(request.recipe)),
+        };
+        if result.is_ok() {
+            *inventory = crafted;
+        }
+        ev_result.send(CraftResult {
+            entity: request.entity,
+            recipe: request.recipe,
+            result,
+        });
+    }
+}
+
+/// Recipes are discovered once the entity holds at least one of each input.
+fn discover_recipes(
+    mut ev_inventory_changed: EventReader<InventoryChanged>,
+    recipes: Res<RecipeBook>,
+    mut crafter_query: Query<(&mut KnownRecipes, &Inventory)>,
+    mut ev_discovered: EventWriter<RecipeDiscovered>,
+) {
+    for changed in ev_inventory_changed.read() {
+        let Ok((mut known, inventory)) = crafter_query.get_mut(changed.entity) else {
+            continue;
+        };
+        for recipe in recipes.recipes.values() {
+            if known.0.contains(&recipe.id) {
+                continue;
+            }
+            if recipe
+                .inputs
+                .iter()
+                .all(|input| inventory.count(input.item) > 0)
+            {
+                known.0.insert(recipe.id);
+                ev_discovered.send(RecipeDiscovered {
+                    entity: changed.entity,
+                    recipe: recipe.id,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Craft, CraftResult, CraftingError, CraftingPlugin, CraftingStation, KnownRecipes,
+        RecipeBook, RecipeDiscovered, RecipeId,
+    };
+    use crate::area::{AreaIdentifier, CurrentArea};
+    use crate::inventory::{Inventory, InventoryError, InventoryPlugin};
+    use crate::item::{test_utils, ItemDatabase, ItemId};
+    use bevy::prelude::*;
+
+    const HERB: ItemId = ItemId(0);
+    const WATER: ItemId = ItemId(1);
+    const POTION: ItemId = ItemId(2);
+    const BREW: RecipeId = RecipeId(0);
+
+    const RECIPES: &str = r#"[
+        (
+            id: 0,
+            name: "Brew potion",
+            inputs: [(item: 0, count: 2), (item: 1, count: 1)],
+            outputs: [(item: 2, count: 1)],
+            station: "Cauldron",
+        ),
+    ]"#;
+
+    fn get_test_app() -> (App, Entity) {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(test_utils::test_database());
+        app.insert_resource(RecipeBook::from_ron(RECIPES).unwrap());
+        app.insert_resource(CurrentArea(0.into()));
+        app.add_plugins(InventoryPlugin);
+        app.add_plugins(CraftingPlugin);
+        let crafter = app
+            .world_mut()
+            .spawn(Inventory::new(3))
+            .insert(KnownRecipes::default())
+            .id();
+        (app, crafter)
+    }
+
+    fn give(app: &mut App, entity: Entity, item: ItemId, count: u32) {
+        let database = app.world().resource::<ItemDatabase>().clone();
+        app.world_mut()
+            .get_mut::<Inventory>(entity)
+            .unwrap()
+            .add(&database, item, count)
+            .unwrap();
+    }
+
+    fn craft(app: &mut App, entity: Entity) -> Result<(), CraftingError> {
+        app.world_mut().send_event(Craft {
+            entity,
+            recipe: BREW,
+        });
+        app.update();
+        let events = app.world().resource::<Events<CraftResult>>();
+        events
+            .get_reader()
+            .read(events)
+            .last()
+            .unwrap()
+            .result
+            .clone()
+    }
+
+    #[test]
+    fn recipes_are_discovered_from_inventory() {
+        let (mut app, crafter) = get_test_app();
+        give(&mut app, crafter, HERB, 1);
+        app.update();
+        app.update();
+        assert!(app
+            .world()
+            .get::<KnownRecipes>(crafter)
+            .unwrap()
+            .0
+            .is_empty());
+        give(&mut app, crafter, WATER, 1);
+        app.update();
+        app.update();
+        assert!(app
+            .world()
+            .get::<KnownRecipes>(crafter)
+            .unwrap()
+            .0
+            .contains(&BREW));
+        let events = app.world().resource::<Events<RecipeDiscovered>>();
+        assert_eq!(events.get_reader().read(events).count(), 1);
+    }
+
+    #[test]
+    fn crafting_requires_station_in_current_area() {
+        let (mut app, crafter) = get_test_app();
+        give(&mut app, crafter, HERB, 3);
+        give(&mut app, crafter, WATER, 1);
+        app.world_mut()
+            .get_mut::<KnownRecipes>(crafter)
+            .unwrap()
+            .0
+            .insert(BREW);
+        let station = app
+            .world_mut()
+            .spawn(CraftingStation("Cauldron".to_string()))
+            .insert(AreaIdentifier(1))
+            .id();
+        assert_eq!(
+            craft(&mut app, crafter),
+            Err(CraftingError::MissingStation("Cauldron".to_string()))
+        );
+
+        app.world_mut()
+            .entity_mut(station)
+            .insert(AreaIdentifier(0));
+        assert_eq!(craft(&mut app, crafter), Ok(()));
+        let inventory = app.world().get::<Inventory>(crafter).unwrap();
+        assert_eq!(inventory.count(HERB), 1);
+        assert_eq!(inventory.count(WATER), 0);
+        assert_eq!(inventory.count(POTION), 1);
+
+        let changed = |app: &App| {
+            app.world()
+                .entity(crafter)
+                .get_ref::<Inventory>()
+                .unwrap()
+                .last_changed()
+        };
+        let last_changed = changed(&app);
+        assert_eq!(
+            craft(&mut app, crafter),
+            Err(CraftingError::Inventory(InventoryError::NotEnoughItems))
+        );
+        assert_eq!(changed(&app), last_changed);
+        assert_eq!(
+            app.world().get::<Inventory>(crafter).unwrap().count(HERB),
+            1
+        );
+    }
+}
@@ -0,0 +1,555 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::area::{AreaTransitionEvent, PassageDestination};
+use crate::flags::{FlagStoragePlugin, WorldFlags};
+use crate::interaction::Interacted;
+use crate::inventory::Inventory;
+use crate::item::ItemId;
+use crate::player::Player;
+use crate::quest::StartQuest;
+use crate::state::GameState;
+
+pub struct DialoguePlugin;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DialogueCondition {
+    Flag(String),
+    HasItem { item: ItemId, count: u32 },
+    Not(Box<DialogueCondition>),
+}
+
+impl DialogueCondition {
+    pub fn is_met(&self, flags: &WorldFlags, inventory: Option<&Inventory>) -> bool {
+        match self {
+            DialogueCondition::Flag(key) => flags.get_bool(key),
+            DialogueCondition::HasItem { item, count } => {
+                inventory.is_some_and(|inventory| inventory.count(*item) >= *count)
+            }
+            DialogueCondition::Not(condition) => !condition.is_met(flags, inventory),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DialogueAction {
+    SetFlag(String, bool),
+    Travel(PassageDestination),
+    StartQuest(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    #[serde(default)]
+    pub actions: Vec<DialogueAction>,
+    /// Index of the node the choice leads to. The dialogue ends if there is none.
+    #[serde(default)]
+    pub next: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DialogueNode {
+    pub speaker: String,
+    pub text: String,
+    /// Asset path of the speaker's portrait.
+    #[serde(default)]
+    pub portrait: Option<String>,
+    /// Actions run when the node is shown.
+    #[serde(default)]
+    pub actions: Vec<DialogueAction>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Node shown after this one if it has no choices.
+    #[serde(default)]
+    pub next: Option<usize>,
+}
+
+/// Dialogue starting at the first node.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DialogueTree {
+    pub id: String,
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl DialogueTree {
+    /// Parses a tree written in RON. Newtypes such as item ids can be written without their
+    /// wrapper and optional fields without `Some`.
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME)
+            .from_str(source)
+    }
+
+    /// Choices of the node whose conditions are all met.
+    pub fn available_choices(
+        &self,
+        node: usize,
+        flags: &WorldFlags,
+        inventory: Option<&Inventory>,
+    ) -> Vec<&DialogueChoice> {
+        self.nodes.get(node).map_or(Vec::new(), |node| {
+            node.choices
+                .iter()
+                .filter(|choice| {
+                    choice
+                        .conditions
+                        .iter()
+                        .all(|condition| condition.is_met(flags, inventory))
+                })
+                .collect()
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Resource)]
+pub struct DialogueLibrary {
+    trees: HashMap<String, DialogueTree>,
+}
+
+impl DialogueLibrary {
+    pub fn new(trees: Vec<DialogueTree>) -> Self {
+        DialogueLibrary {
+            trees: trees
+                .into_iter()
+                .map(|tree| (tree.id.clone(), tree))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DialogueTree> {
+        self.trees.get(id)
+    }
+}
+
+/// Dialogue started by interacting with the entity.
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct Dialogue(pub String);
+
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct ActiveDialogue {
+    pub tree: String,
+    pub node: usize,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct StartDialogue {
+    pub tree: String,
+}
+
+/// Answers the current node, with the index of one of its available choices if it has any.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct DialogueResponse {
+    pub choice: Option<usize>,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct DialogueStarted {
+    pub tree: String,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct DialogueEnded {
+    pub tree: String,
+}
+
+#[derive(Component)]
+struct DialogueBox;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FlagStoragePlugin>() {
+            app.add_plugins(FlagStoragePlugin);
+        }
+        app.init_resource::<DialogueLibrary>()
+            .add_event::<StartDialogue>()
+            .add_event::<DialogueResponse>()
+            .add_event::<DialogueStarted>()
+            .add_event::<DialogueEnded>()
+            .add_systems(Update, dialogue_interaction)
+            .add_systems(Update, start_dialogue.after(dialogue_interaction))
+            .add_systems(
+                Update,
+                (dialogue_input, advance_dialogue)
+                    .chain()
+                    .run_if(resource_exists::<ActiveDialogue>),
+            )
+            .add_systems(
+                Update,
+                dialogue_box
+                    .after(advance_dialogue)
+                    .run_if(resource_exists_and_changed::<ActiveDialogue>),
+            );
+    }
+}
+
+fn dialogue_interaction(
+    mut ev_interacted: EventReader<Interacted>,
+    dialogue_query: Query<&Dialogue>,
+    mut ev_start_dialogue: EventWriter<StartDialogue>,
+) {
+    for interacted in ev_interacted.read() {
+        if let Ok(dialogue) = dialogue_query.get(interacted.entity) {
+            ev_start_dialogue.send(StartDialogue {
+                tree: dialogue.0.clone(),
+            });
+        }
+    }
+}
+
+/// Everything dialogue actions can affect.
+#[derive(SystemParam)]
+struct DialogueEffects<'w> {
+    flags: ResMut<'w, WorldFlags>,
+    ev_area_transition: EventWriter<'w, AreaTransitionEvent>,
+    ev_start_quest: EventWriter<'w, StartQuest>,
+}
+
+impl DialogueEffects<'_> {
+    fn apply(&mut self, actions: &[DialogueAction]) {
+        for action in actions {
+            match action {
+                DialogueAction::SetFlag(key, value) => self.flags.set_bool(key.clone(), *value),
+                DialogueAction::Travel(destination) => {
+                    self.ev_area_transition
+                        .send(AreaTransitionEvent(*destination));
+                }
+                DialogueAction::StartQuest(quest) => {
+                    self.ev_start_quest.send(StartQuest {
+                        quest: quest.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn start_dialogue(
+    mut commands: Commands,
+    mut ev_start_dialogue: EventReader<StartDialogue>,
+    library: Res<DialogueLibrary>,
+    active_dialogue: Option<Res<ActiveDialogue>>,
+    mut effects: DialogueEffects,
+    mut next_state: Option<ResMut<NextState<GameState>>>,
+    mut ev_started: EventWriter<DialogueStarted>,
+) {
+    let Some(start) = ev_start_dialogue.read().last() else {
+        return;
+    };
+    if active_dialogue.is_some() {
+        return;
+    }
+    let Some(node) = library.get(&start.tree).and_then(|tree| tree.nodes.first()) else {
+        warn!("Unknown dialogue {}", start.tree);
+        return;
+    };
+    effects.apply(&node.actions);
+    commands.insert_resource(ActiveDialogue {
+        tree: start.tree.clone(),
+        node: 0,
+    });
+    if let Some(next_state) = next_state.as_mut() {
+        next_state.set(GameState::Dialogue);
+    }
+    ev_started.send(DialogueStarted {
+        tree: start.tree.clone(),
+    });
+}
+
+const CHOICE_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+fn dialogue_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut ev_response: EventWriter<DialogueResponse>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Enter]) {
+        ev_response.send(DialogueResponse { choice: None });
+    }
+    for (choice, key) in CHOICE_KEYS.iter().enumerate() {
+        if keyboard_input.just_pressed(*key) {
+            ev_response.send(DialogueResponse {
+                choice: Some(choice),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn advance_dialogue(
+    mut commands: Commands,
+    mut ev_response: EventReader<DialogueResponse>,
+    library: Res<DialogueLibrary>,
+    mut active_dialogue: ResMut<ActiveDialogue>,
+    mut effects: DialogueEffects,
+    mut next_state: Option<ResMut<NextState<GameState>>>,
+    inventory_query: Query<&Inventory, With<Player>>,
+    box_query: Query<Entity, With<DialogueBox>>,
+    mut ev_ended: EventWriter<DialogueEnded>,
+) {
+    let Some(response) = ev_response.read().last() else {
+        return;
+    };
+    let Some(tree) = library.get(&active_dialogue.tree) else {
+        return;
+    };
+    let choices = tree.available_choices(
+        active_dialogue.node,
+        &effects.flags,
+        inventory_query.get_single().ok(),
+    );
+    let next = if choices.is_empty() {
+        tree.nodes
+            .get(active_dialogue.node)
+            .and_then(|node| node.next)
+    } else {
+        let Some(choice) = response.choice.and_then(|choice| choices.get(choice)) else {
+            return;
+        };
+        let next = choice.next;
+        effects.apply(&choice.actions);
+        next
+    };
+    match next.and_then(|next| tree.nodes.get(next).map(|node| (next, node))) {
+        Some((next, node)) => {
+            effects.apply(&node.actions);
+            active_dialogue.node = next;
+        }
+        None => {
+            commands.remove_resource::<ActiveDialogue>();
+            for dialogue_box in box_query.iter() {
+                commands.entity(dialogue_box).despawn_recursive();
+            }
+            if let Some(next_state) = next_state.as_mut() {
+                next_state.set(GameState::Playing);
+            }
+            ev_ended.send(DialogueEnded {
+                tree: active_dialogue.tree.clone(),
+            });
+        }
+    }
+}
+
+fn dialogue_box(
+    mut commands: Commands,
+    library: Res<DialogueLibrary>,
+    active_dialogue: Res<ActiveDialogue>,
+    flags: Res<WorldFlags>,
+    asset_server: Option<Res<AssetServer>>,
+    inventory_query: Query<&Inventory, With<Player>>,
+    box_query: Query<Entity, With<DialogueBox>>,
+) {
+    for dialogue_box in box_query.iter() {
+        commands.entity(dialogue_box).despawn_recursive();
+    }
+    let Some(tree) = library.get(&active_dialogue.tree) else {
+        return;
+    };
+    let Some(node) = tree.nodes.get(active_dialogue.node) else {
+        return;
+    };
+    let choices = tree.available_choices(
+        active_dialogue.node,
+        &flags,
+        inventory_query.get_single().ok(),
+    );
+    let mut lines = vec![node.speaker.clone(), node.text.clone()];
+    if choices.is_empty() {
+        lines.push("Space: continue".to_string());
+    }
+    for (i, choice) in choices.iter().enumerate() {
+        lines.push(format!("{}: {}", i + 1, choice.text));
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(40.),
+                right: Val::Px(40.),
+                bottom: Val::Px(40.),
+                padding: UiRect::all(Val::Px(16.)),
+                column_gap: Val::Px(16.),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.8).into(),
+            ..default()
+        })
+        .insert(DialogueBox)
+        .with_children(|parent| {
+            if let (Some(portrait), Some(asset_server)) = (&node.portrait, &asset_server) {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(96.),
+                        height: Val::Px(96.),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load(portrait.clone())),
+                    ..default()
+                });
+            }
+            parent.spawn(TextBundle::from_section(
+                lines.join("\n"),
+                TextStyle {
+                    font_size: 20.,
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ActiveDialogue, DialogueLibrary, DialoguePlugin, DialogueResponse, DialogueTree,
+        StartDialogue,
+    };
+    use crate::area::{AreaTransitionEvent, PassageDestination};
+    use crate::flags::WorldFlags;
+    use crate::interaction::Interacted;
+    use crate::inventory::Inventory;
+    use crate::item::{test_utils, ItemDatabase};
+    use crate::player::Player;
+    use crate::quest::StartQuest;
+    use crate::state::{GameState, GameStatePlugin};
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::plugin::{NoUserData, RapierPhysicsPlugin};
+
+    const ELDER: &str = r#"(
+        id: "elder",
+        nodes: [
+            (
+                speaker: "Elder",
+                text: "Welcome, traveller.",
+                portrait: "portraits/elder.png",
+                choices: [
+                    (text: "Any news?", conditions: [Not(Flag("heard_news"))], next: 1),
+                    (text: "Here's a herb.", conditions: [HasItem(item: 0, count: 1)], next: 2),
+                    (text: "Goodbye."),
+                ],
+            ),
+            (
+                speaker: "Elder",
+                text: "Slimes have been seen to the east.",
+                actions: [SetFlag("heard_news", true)],
+            ),
+            (
+                speaker: "Elder",
+                text: "Thank you! Let me take you home.",
+                actions: [Travel((1, (
+                    translation: (20.0, 20.0, 1.0),
+                    rotation: (0.0, 0.0, 0.0, 1.0),
+                    scale: (1.0, 1.0, 1.0),
+                )))],
+            ),
+        ],
+    )"#;
+
+    fn get_test_app() -> App {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        app.insert_state(GameState::Playing);
+        app.add_plugins(GameStatePlugin);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_event::<Interacted>();
+        app.add_event::<AreaTransitionEvent>();
+        app.add_event::<StartQuest>();
+        app.insert_resource(DialogueLibrary::new(vec![
+            DialogueTree::from_ron(ELDER).unwrap()
+        ]));
+        app.add_plugins(DialoguePlugin);
+        app
+    }
+
+    fn herb_database() -> ItemDatabase {
+        test_utils::test_database()
+    }
+
+    fn respond(app: &mut App, choice: Option<usize>) {
+        app.world_mut().send_event(DialogueResponse { choice });
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn choices_depend_on_flags_and_items() {
+        let tree = DialogueTree::from_ron(ELDER).unwrap();
+        let mut flags = WorldFlags::default();
+        let mut inventory = Inventory::new(1);
+        let texts = |flags: &WorldFlags, inventory: &Inventory| -> Vec<String> {
+            tree.available_choices(0, flags, Some(inventory))
+                .iter()
+                .map(|choice| choice.text.clone())
+                .collect()
+        };
+        assert_eq!(texts(&flags, &inventory), vec!["Any news?", "Goodbye."]);
+
+        flags.set_bool("heard_news", true);
+        inventory.add(&herb_database(), 0.into(), 1).unwrap();
+        assert_eq!(
+            texts(&flags, &inventory),
+            vec!["Here's a herb.", "Goodbye."]
+        );
+    }
+
+    #[test]
+    fn dialogue_suspends_play_and_runs_actions() {
+        let mut app = get_test_app();
+        let player = app.world_mut().spawn(Player::default()).id();
+        app.world_mut().send_event(StartDialogue {
+            tree: "elder".to_string(),
+        });
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Dialogue
+        );
+
+        respond(&mut app, Some(0));
+        assert_eq!(app.world().resource::<ActiveDialogue>().node, 1);
+        assert!(app.world().resource::<WorldFlags>().get_bool("heard_news"));
+
+        respond(&mut app, None);
+        assert!(app.world().get_resource::<ActiveDialogue>().is_none());
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Playing
+        );
+
+        let mut inventory = Inventory::new(1);
+        inventory.add(&herb_database(), 0.into(), 1).unwrap();
+        app.world_mut().entity_mut(player).insert(inventory);
+        app.world_mut().send_event(StartDialogue {
+            tree: "elder".to_string(),
+        });
+        app.update();
+        respond(&mut app, Some(0));
+        assert_eq!(app.world().resource::<ActiveDialogue>().node, 2);
+        let events = app.world().resource::<Events<AreaTransitionEvent>>();
+        assert_eq!(
+            events.get_reader().read(events).last(),
+            Some(&AreaTransitionEvent(PassageDestination(
+                1.into(),
+                Transform::from_xyz(20., 20., 1.)
+            )))
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use crate::area::{AreaEntered, CurrentArea, GameAreas};
+use crate::battle::{EncounterEvent, EnemyGroup};
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::state::is_playing;
+
+pub struct EncounterPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EncounterRegion {
+    WholeArea,
+    Rect(Rect),
+}
+
+impl EncounterRegion {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            EncounterRegion::WholeArea => true,
+            EncounterRegion::Rect(rect) => rect.contains(point),
+        }
+    }
+}
+
+/// Enemy groups paired with their relative weights.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EncounterTable(pub Vec<(u32, EnemyGroup)>);
+
+impl EncounterTable {
+    pub fn choose(&self, rng: &mut impl Rng) -> Option<&EnemyGroup> {
+        let total: u32 = self.0.iter().map(|(weight, _)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0..total);
+        for (weight, group) in &self.0 {
+            if roll < *weight {
+                return Some(group);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncounterZone {
+    pub region: EncounterRegion,
+    pub table: EncounterTable,
+    /// Distance the player walks between encounter rolls.
+    pub step_distance: f32,
+    /// Probability of each roll starting an encounter.
+    pub chance: f32,
+}
+
+#[derive(Debug, Default, Resource)]
+struct EncounterProgress {
+    last_position: Option<Vec2>,
+    distance: f32,
+}
+
+impl Plugin for EncounterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .init_resource::<EncounterProgress>()
+            .add_systems(Update, reset_encounter_progress)
+            .add_systems(
+                Update,
+                encounter_zones
+                    .after(reset_encounter_progress)
+                    .run_if(is_playing),
+            );
+    }
+}
+
+fn reset_encounter_progress(
+    mut ev_area_entered: EventReader<AreaEntered>,
+    mut progress: ResMut<EncounterProgress>,
+) {
+    if ev_area_entered.read().next().is_some() {
+        *progress = EncounterProgress::default();
+    }
+}
+
+fn encounter_zones(
+    game_areas: Res<GameAreas>,
+    current_area: Option<Res<CurrentArea>>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    mut progress: ResMut<EncounterProgress>,
+    mut rng: ResMut<GameRng>,
+    mut ev_encounter: EventWriter<EncounterEvent>,
+) {
+    let (Some(current_area), Ok((transform, velocity))) = (current_area, player_query.get_single())
+    else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let last_position = progress.last_position.replace(position);
+    if velocity.linvel == Vec2::ZERO {
+        return;
+    }
+    let Some(zone) = game_areas.get(current_area.0).and_then(|area| {
+        area.encounter_zones()
+            .iter()
+            .find(|zone| zone.region.contains(position))
+    }) else {
+        return;
+    };
+    if let Some(last_position) = last_position.filter(|last| zone.region.contains(*last)) {
+        progress.distance += last_position.distance(position);
+    }
+    while progress.distance >= zone.step_distance {
+        progress.distance -= zone.step_distance;
+        if rng.0.gen::<f32>() < zone.chance {
+            if let Some(group) = zone.table.choose(&mut rng.0) {
+                ev_encounter.send(EncounterEvent {
+                    group: group.clone(),
+                    source: None,
+                });
+            }
+            progress.distance = 0.;
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncounterPlugin, EncounterRegion, EncounterTable, EncounterZone};
+    use crate::area::{Area, CurrentArea, GameAreas};
+    use crate::battle::{BattleEnemy, EncounterEvent, EnemyGroup};
+    use crate::player::Player;
+    use crate::rng::GameRng;
+    use crate::stats::Attributes;
+    use bevy::prelude::*;
+    use bevy_rapier2d::prelude::*;
+
+    fn group(name: &str) -> EnemyGroup {
+        EnemyGroup(vec![BattleEnemy {
+            name: name.to_string(),
+            attributes: Attributes::default(),
+            experience: 1,
+        }])
+    }
+
+    #[test]
+    fn table_choice_is_weighted_and_reproducible() {
+        let table = EncounterTable(vec![
+            (3, group("Common")),
+            (1, group("Rare")),
+            (0, group("Never")),
+        ]);
+        let roll = |seed| {
+            let mut rng = GameRng::seeded(seed);
+            (0..400)
+                .map(|_| table.choose(&mut rng.0).unwrap().0[0].name.clone())
+                .collect::<Vec<_>>()
+        };
+        let names = roll(7);
+        assert_eq!(names, roll(7));
+        let common = names.iter().filter(|name| *name == "Common").count();
+        assert!(common > 250 && common < 350);
+        assert!(!names.iter().any(|name| name == "Never"));
+        assert!(EncounterTable::default()
+            .choose(&mut GameRng::seeded(0).0)
+            .is_none());
+    }
+
+    #[test]
+    fn walking_through_zone_triggers_encounter() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<EncounterEvent>();
+        app.add_event::<crate::area::AreaEntered>();
+        app.add_plugins(EncounterPlugin);
+        app.insert_resource(GameRng::seeded(1));
+        app.insert_resource(CurrentArea(0.into()));
+        app.insert_resource(GameAreas::new(vec![Area::new(Color::BLACK, vec![])
+            .with_encounters(vec![EncounterZone {
+                region: EncounterRegion::Rect(Rect::new(0., 0., 100., 100.)),
+                table: EncounterTable(vec![(1, group("Slime"))]),
+                step_distance: 10.,
+                chance: 1.,
+            }])]));
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(Transform::from_xyz(150., 50., 0.))
+            .insert(Velocity::linear(Vec2::X))
+            .id();
+
+        let walk_to = |app: &mut App, x: f32| {
+            app.world_mut()
+                .get_mut::<Transform>(player)
+                .unwrap()
+                .translation
+                .x = x;
+            app.update();
+            let events = app.world().resource::<Events<EncounterEvent>>();
+            events.get_reader().read(events).count()
+        };
+        assert_eq!(walk_to(&mut app, 150.), 0);
+        // Outside of the zone
+        assert_eq!(walk_to(&mut app, 120.), 0);
+        assert_eq!(walk_to(&mut app, 95.), 0);
+        assert_eq!(walk_to(&mut app, 90.), 0);
+        assert_eq!(walk_to(&mut app, 80.), 1);
+    }
+}
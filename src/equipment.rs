@@ -0,0 +1,320 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::{Inventory, InventoryError};
+use crate::item::{ItemDatabase, ItemId};
+use crate::stats::{ModifierKind, ModifierSource, Stat, StatModifier, StatModifiers};
+
+pub struct EquipmentPlugin;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armour,
+    Accessory,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 3] = [
+        EquipmentSlot::Weapon,
+        EquipmentSlot::Armour,
+        EquipmentSlot::Accessory,
+    ];
+
+    fn modifier_source(self) -> ModifierSource {
+        ModifierSource::Equipment(self as usize)
+    }
+}
+
+/// Sprite drawn over the wearer while the item is equipped.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EquippedSprite {
+    /// sRGBA colour.
+    pub color: [f32; 4],
+    pub size: [f32; 2],
+    pub offset: [f32; 2],
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+    pub modifiers: Vec<(Stat, ModifierKind)>,
+    /// Factor applied to the wearer's collider.
+    pub collider_scale: f32,
+    pub sprite: Option<EquippedSprite>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EquipmentError {
+    NotEquippable(ItemId),
+    EmptySlot(EquipmentSlot),
+    Inventory(InventoryError),
+}
+
+impl fmt::Display for EquipmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquipmentError::NotEquippable(item) => write!(f, "item {} can't be equipped", item.0),
+            EquipmentError::EmptySlot(slot) => write!(f, "nothing is equipped as {slot:?}"),
+            EquipmentError::Inventory(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EquipmentError {}
+
+impl From<InventoryError> for EquipmentError {
+    fn from(error: InventoryError) -> Self {
+        EquipmentError::Inventory(error)
+    }
+}
+
+#[derive(Clone, Component, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Equipment {
+    pub weapon: Option<ItemId>,
+    pub armour: Option<ItemId>,
+    pub accessory: Option<ItemId>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipmentSlot) -> Option<ItemId> {
+        *self.slot(slot)
+    }
+
+    fn slot(&self, slot: EquipmentSlot) -> &Option<ItemId> {
+        match slot {
+            EquipmentSlot::Weapon => &self.weapon,
+            EquipmentSlot::Armour => &self.armour,
+            EquipmentSlot::Accessory => &self.accessory,
+        }
+    }
+
+    fn slot_mut(&mut self, slot: EquipmentSlot) -> &mut Option<ItemId> {
+        match slot {
+            EquipmentSlot::Weapon => &mut self.weapon,
+            EquipmentSlot::Armour => &mut self.armour,
+            EquipmentSlot::Accessory => &mut self.accessory,
+        }
+    }
+
+    /// Equips an item taken from the inventory, putting whatever was in its slot back.
+    pub fn equip(
+        &mut self,
+        inventory: &mut Inventory,
+        database: &ItemDatabase,
+        item: ItemId,
+    ) -> Result<(), EquipmentError> {
+        let slot = database
+            .get(item)
+            .and_then(|definition| definition.equipment.as_ref())
+            .ok_or(EquipmentError::NotEquippable(item))?
+            .slot;
+        inventory.remove(item, 1)?;
+        if let Some(previous) = self.get(slot) {
+            if let Err(error) = inventory.add(database, previous, 1) {
+                inventory.add(database, item, 1)?;
+                return Err(error.into());
+            }
+        }
+        *self.slot_mut(slot) = Some(item);
+        Ok(())
+    }
+
+    pub fn unequip(
+        &mut self,
+        inventory: &mut Inventory,
+        database: &ItemDatabase,
+        slot: EquipmentSlot,
+    ) -> Result<(), EquipmentError> {
+        let item = self.get(slot).ok_or(EquipmentError::EmptySlot(slot))?;
+        inventory.add(database, item, 1)?;
+        *self.slot_mut(slot) = None;
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+struct EquipmentOverlay;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_equipment);
+    }
+}
+
+fn apply_equipment(
+    mut commands: Commands,
+    database: Res<ItemDatabase>,
+    mut equipment_query: Query<
+        (
+            Entity,
+            &Equipment,
+            &mut StatModifiers,
+            Option<&mut Collider>,
+        ),
+        Changed<Equipment>,
+    >,
+    overlay_query: Query<(Entity, &Parent), With<EquipmentOverlay>>,
+) {
+    for (entity, equipment, mut modifiers, collider) in equipment_query.iter_mut() {
+        for (overlay, parent) in overlay_query.iter() {
+            if parent.get() == entity {
+                commands.entity(overlay).despawn_recursive();
+            }
+        }
+        let mut collider_scale = 1.;
+        for (i, slot) in EquipmentSlot::ALL.into_iter().enumerate() {
+            modifiers.remove_source(slot.modifier_source());
+            let Some(equippable) = equipment
+                .get(slot)
+                .and_then(|item| database.get(item))
+                .and_then(|definition| definition.equipment.as_ref())
+            else {
+                continue;
+            };
+            for &(stat, kind) in &equippable.modifiers {
+                modifiers.add(StatModifier {
+                    source: slot.modifier_source(),
+                    stat,
+                    kind,
+                });
+            }
+            collider_scale *= equippable.collider_scale;
+            if let Some(sprite) = equippable.sprite {
+                let [r, g, b, a] = sprite.color;
+                let [x, y] = sprite.offset;
+                let overlay = commands
+                    .spawn(SpriteBundle {
+                        transform: Transform::from_xyz(x, y, 0.1 * (i + 1) as f32),
+                        sprite: Sprite {
+                            color: Color::srgba(r, g, b, a),
+                            custom_size: Some(sprite.size.into()),
+                            ..default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(EquipmentOverlay)
+                    .id();
+                commands.entity(entity).add_child(overlay);
+            }
+        }
+        if let Some(mut collider) = collider {
+            collider.set_scale(Vec2::splat(collider_scale), 4);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Equipment, EquipmentError, EquipmentSlot, Equippable, EquippedSprite};
+    use crate::inventory::Inventory;
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition, ItemId};
+    use crate::stats::{Attributes, DerivedStats, ModifierKind, Stat, StatModifiers, StatsBundle};
+    use bevy::prelude::*;
+    use bevy_rapier2d::prelude::*;
+
+    const BOOTS: ItemId = ItemId(0);
+    const SANDALS: ItemId = ItemId(1);
+    const STONE: ItemId = ItemId(2);
+
+    fn get_test_database() -> ItemDatabase {
+        let boots = |id, name: &str, speed| ItemDefinition {
+            stack_size: 1,
+            category: ItemCategory::Equipment,
+            equipment: Some(Equippable {
+                slot: EquipmentSlot::Accessory,
+                modifiers: vec![(Stat::MoveSpeed, ModifierKind::Percent(speed))],
+                collider_scale: 0.5,
+                sprite: Some(EquippedSprite {
+                    color: [1., 1., 1., 1.],
+                    size: [10., 10.],
+                    offset: [0., -20.],
+                }),
+            }),
+            ..material(id, name)
+        };
+        ItemDatabase::new(vec![
+            boots(BOOTS, "Boots", 0.25),
+            boots(SANDALS, "Sandals", 0.1),
+            material(STONE, "Stone"),
+        ])
+    }
+
+    #[test]
+    fn equipping_swaps_with_inventory() {
+        let database = get_test_database();
+        let mut inventory = Inventory::new(2);
+        inventory.add(&database, BOOTS, 1).unwrap();
+        inventory.add(&database, SANDALS, 1).unwrap();
+        let mut equipment = Equipment::default();
+
+        equipment.equip(&mut inventory, &database, BOOTS).unwrap();
+        assert_eq!(equipment.get(EquipmentSlot::Accessory), Some(BOOTS));
+        assert_eq!(inventory.count(BOOTS), 0);
+
+        equipment.equip(&mut inventory, &database, SANDALS).unwrap();
+        assert_eq!(equipment.get(EquipmentSlot::Accessory), Some(SANDALS));
+        assert_eq!(inventory.count(BOOTS), 1);
+
+        assert_eq!(
+            equipment.equip(&mut inventory, &database, STONE),
+            Err(EquipmentError::NotEquippable(STONE))
+        );
+        equipment
+            .unequip(&mut inventory, &database, EquipmentSlot::Accessory)
+            .unwrap();
+        assert_eq!(equipment, Equipment::default());
+        assert_eq!(inventory.count(SANDALS), 1);
+    }
+
+    #[test]
+    fn equipment_applies_modifiers_collider_and_sprite() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(get_test_database());
+        app.add_plugins(super::EquipmentPlugin);
+        app.add_plugins(crate::stats::StatsPlugin);
+        let entity = app
+            .world_mut()
+            .spawn(StatsBundle::new(Attributes::default()))
+            .insert(Collider::cuboid(10., 10.))
+            .insert(Equipment {
+                accessory: Some(BOOTS),
+                ..default()
+            })
+            .id();
+        app.update();
+        app.update();
+
+        let base_speed =
+            DerivedStats::compute(&Attributes::default(), &StatModifiers::default()).move_speed;
+        let world = app.world();
+        assert_eq!(
+            world.get::<DerivedStats>(entity).unwrap().move_speed,
+            base_speed * 1.25
+        );
+        assert_eq!(
+            world.get::<Collider>(entity).unwrap().scale(),
+            Vec2::splat(0.5)
+        );
+        assert_eq!(world.get::<Children>(entity).unwrap().len(), 1);
+
+        app.world_mut()
+            .get_mut::<Equipment>(entity)
+            .unwrap()
+            .accessory = None;
+        app.update();
+        app.update();
+        let world = app.world();
+        assert_eq!(
+            world.get::<DerivedStats>(entity).unwrap().move_speed,
+            base_speed
+        );
+        assert_eq!(world.get::<Collider>(entity).unwrap().scale(), Vec2::ONE);
+        assert!(world.get::<Children>(entity).is_none_or(|c| c.is_empty()));
+    }
+}
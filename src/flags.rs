@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::area::AreaIdentifier;
+
+pub struct FlagsPlugin;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FlagValue {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum FlagScope {
+    Global,
+    Area(AreaIdentifier),
+}
+
+/// Named values shared by dialogue, quests and other world logic.
+#[derive(Clone, Debug, Default, Deserialize, Resource, Serialize)]
+pub struct WorldFlags {
+    global: HashMap<String, FlagValue>,
+    areas: HashMap<AreaIdentifier, HashMap<String, FlagValue>>,
+    #[serde(skip)]
+    changes: Vec<FlagChanged>,
+}
+
+// Pending changes aren't part of the flags' value.
+impl PartialEq for WorldFlags {
+    fn eq(&self, other: &Self) -> bool {
+        self.global == other.global && self.areas == other.areas
+    }
+}
+
+impl WorldFlags {
+    pub fn get(&self, scope: FlagScope, key: &str) -> Option<&FlagValue> {
+        match scope {
+            FlagScope::Global => self.global.get(key),
+            FlagScope::Area(area) => self.areas.get(&area)?.get(key),
+        }
+    }
+
+    /// Sets a flag, recording a [`FlagChanged`] if its value differs from the previous one.
+    pub fn set(&mut self, scope: FlagScope, key: impl Into<String>, value: FlagValue) {
+        let key = key.into();
+        let flags = match scope {
+            FlagScope::Global => &mut self.global,
+            FlagScope::Area(area) => self.areas.entry(area).or_default(),
+        };
+        let previous = flags.insert(key.clone(), value.clone());
+        if previous.as_ref() != Some(&value) {
+            self.changes.push(FlagChanged {
+                scope,
+                key,
+                previous,
+                value: Some(value),
+            });
+        }
+    }
+
+    pub fn remove(&mut self, scope: FlagScope, key: &str) -> Option<FlagValue> {
+        let previous = match scope {
+            FlagScope::Global => self.global.remove(key),
+            FlagScope::Area(area) => self.areas.get_mut(&area)?.remove(key),
+        };
+        if previous.is_some() {
+            self.changes.push(FlagChanged {
+                scope,
+                key: key.to_string(),
+                previous: previous.clone(),
+                value: None,
+            });
+        }
+        previous
+    }
+
+    /// Global boolean flag. Unset flags and flags of other types read as `false`.
+    pub fn get_bool(&self, key: &str) -> bool {
+        matches!(
+            self.get(FlagScope::Global, key),
+            Some(FlagValue::Bool(true))
+        )
+    }
+
+    pub fn set_bool(&mut self, key: impl Into<String>, value: bool) {
+        self.set(FlagScope::Global, key, FlagValue::Bool(value));
+    }
+
+    /// Global integer flag. Unset flags and flags of other types read as 0.
+    pub fn get_int(&self, key: &str) -> i64 {
+        match self.get(FlagScope::Global, key) {
+            Some(FlagValue::Int(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    pub fn set_int(&mut self, key: impl Into<String>, value: i64) {
+        self.set(FlagScope::Global, key, FlagValue::Int(value));
+    }
+
+    pub fn add_int(&mut self, key: impl Into<String>, amount: i64) {
+        let key = key.into();
+        let value = self.get_int(&key).saturating_add(amount);
+        self.set_int(key, value);
+    }
+
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        match self.get(FlagScope::Global, key) {
+            Some(FlagValue::Text(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn set_text(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.set(FlagScope::Global, key, FlagValue::Text(value.into()));
+    }
+}
+
+/// Sent in [`PostUpdate`] for every flag that was set to a new value or removed.
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct FlagChanged {
+    pub scope: FlagScope,
+    pub key: String,
+    pub previous: Option<FlagValue>,
+    pub value: Option<FlagValue>,
+}
+
+impl Plugin for FlagsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FlagStoragePlugin>() {
+            app.add_plugins(FlagStoragePlugin);
+        }
+        app.add_event::<FlagChanged>()
+            .add_systems(PostUpdate, send_flag_changes);
+    }
+}
+
+/// Adds [`WorldFlags`] for plugins that read or write flags without needing [`FlagsPlugin`].
+/// Without it nothing sends the recorded changes, so they are discarded every frame instead.
+pub(crate) struct FlagStoragePlugin;
+
+impl Plugin for FlagStoragePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldFlags>().add_systems(
+            Last,
+            discard_flag_changes.run_if(not(resource_exists::<Events<FlagChanged>>)),
+        );
+    }
+}
+
+fn send_flag_changes(mut flags: ResMut<WorldFlags>, mut ev_flag_changed: EventWriter<FlagChanged>) {
+    if flags.changes.is_empty() {
+        return;
+    }
+    ev_flag_changed.send_batch(flags.changes.drain(..));
+}
+
+fn discard_flag_changes(mut flags: ResMut<WorldFlags>) {
+    if !flags.changes.is_empty() {
+        flags.changes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlagChanged, FlagScope, FlagStoragePlugin, FlagValue, FlagsPlugin, WorldFlags};
+    use crate::area::AreaIdentifier;
+    use bevy::prelude::*;
+
+    #[test]
+    fn flags_are_scoped() {
+        let mut flags = WorldFlags::default();
+        let area = FlagScope::Area(AreaIdentifier(1));
+        flags.set_bool("door_open", true);
+        flags.set(area, "door_open", FlagValue::Bool(false));
+        flags.add_int("slimes", 2);
+        flags.add_int("slimes", 3);
+        flags.set_text("name", "Ada");
+
+        assert!(flags.get_bool("door_open"));
+        assert_eq!(flags.get(area, "door_open"), Some(&FlagValue::Bool(false)));
+        assert_eq!(
+            flags.get(FlagScope::Area(AreaIdentifier(0)), "door_open"),
+            None
+        );
+        assert_eq!(flags.get_int("slimes"), 5);
+        assert_eq!(flags.get_text("name"), Some("Ada"));
+        assert!(!flags.get_bool("slimes"));
+
+        let serialized = ron::to_string(&flags).unwrap();
+        let deserialized: WorldFlags = ron::from_str(&serialized).unwrap();
+        assert_eq!(
+            deserialized.get(area, "door_open"),
+            Some(&FlagValue::Bool(false))
+        );
+        assert_eq!(deserialized.get_int("slimes"), 5);
+    }
+
+    #[test]
+    fn changes_send_events() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(FlagsPlugin);
+        let mut flags = app.world_mut().resource_mut::<WorldFlags>();
+        flags.set_bool("met_elder", true);
+        flags.set_bool("met_elder", true);
+        flags.remove(FlagScope::Global, "met_elder");
+        app.update();
+
+        let events = app.world().resource::<Events<FlagChanged>>();
+        let changes: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|change| (change.previous.clone(), change.value.clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (None, Some(FlagValue::Bool(true))),
+                (Some(FlagValue::Bool(true)), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_are_discarded_without_flags_plugin() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(FlagStoragePlugin);
+        let mut flags = app.world_mut().resource_mut::<WorldFlags>();
+        flags.set_bool("met_elder", true);
+        let snapshot = flags.clone();
+        flags.set_int("slimes", 1);
+        flags.remove(FlagScope::Global, "slimes");
+        assert_eq!(*flags, snapshot);
+        app.update();
+
+        assert!(app.world().resource::<WorldFlags>().changes.is_empty());
+        assert!(app.world().get_resource::<Events<FlagChanged>>().is_none());
+    }
+}
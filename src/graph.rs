@@ -0,0 +1,181 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::area::{AreaIdentifier, GameAreas};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AreaNode {
+    pub area: AreaIdentifier,
+    pub name: Option<String>,
+    /// The area's passages lead to at most one other area.
+    pub dead_end: bool,
+}
+
+/// A passage from one area to another.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AreaLink {
+    pub from: AreaIdentifier,
+    pub to: AreaIdentifier,
+    pub required_level: Option<u32>,
+    /// No passage in the destination leads back.
+    pub one_way: bool,
+    /// The destination isn't one of the areas, so the passage can't be used.
+    pub dangling: bool,
+}
+
+/// Connectivity of [`GameAreas`] through their passages.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AreaGraph {
+    pub areas: Vec<AreaNode>,
+    pub links: Vec<AreaLink>,
+}
+
+impl AreaGraph {
+    pub fn new(game_areas: &GameAreas) -> Self {
+        let destinations = |area: AreaIdentifier| -> BTreeSet<AreaIdentifier> {
+            game_areas.get(area).map_or_else(BTreeSet::new, |area| {
+                area.passages()
+                    .iter()
+                    .map(|passage| passage.destination().0)
+                    .filter(|&destination| game_areas.get(destination).is_some())
+                    .collect()
+            })
+        };
+        let mut areas = vec![];
+        let mut links = vec![];
+        for (identifier, area) in game_areas.iter() {
+            let neighbours = destinations(identifier);
+            let other_areas = neighbours.iter().filter(|&&other| other != identifier);
+            areas.push(AreaNode {
+                area: identifier,
+                name: area.name().map(str::to_string),
+                dead_end: other_areas.count() <= 1,
+            });
+            for passage in area.passages() {
+                let to = passage.destination().0;
+                let dangling = game_areas.get(to).is_none();
+                links.push(AreaLink {
+                    from: identifier,
+                    to,
+                    required_level: passage.required_level().map(|level| level.0),
+                    one_way: !dangling && !destinations(to).contains(&identifier),
+                    dangling,
+                });
+            }
+        }
+        AreaGraph { areas, links }
+    }
+
+    pub fn dead_ends(&self) -> impl Iterator<Item = &AreaNode> {
+        self.areas.iter().filter(|node| node.dead_end)
+    }
+
+    pub fn one_way_links(&self) -> impl Iterator<Item = &AreaLink> {
+        self.links.iter().filter(|link| link.one_way)
+    }
+
+    pub fn dangling_links(&self) -> impl Iterator<Item = &AreaLink> {
+        self.links.iter().filter(|link| link.dangling)
+    }
+
+    /// Graphviz description of the graph. Dead ends are drawn in orange, one-way passages as
+    /// dashed red arrows and passages to missing areas as bold purple arrows.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph areas {\n");
+        for node in &self.areas {
+            let label = node
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Area {}", node.area.0));
+            let style = if node.dead_end { ", color=orange" } else { "" };
+            let _ = writeln!(dot, "    {} [label={label:?}{style}];", node.area.0);
+        }
+        for link in &self.links {
+            let mut attributes = vec![];
+            if let Some(level) = link.required_level {
+                attributes.push(format!("label=\"level {level}\""));
+            }
+            if link.one_way {
+                attributes.push("style=dashed, color=red".to_string());
+            }
+            if link.dangling {
+                attributes.push("style=bold, color=purple".to_string());
+            }
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            let _ = writeln!(dot, "    {} -> {}{attributes};", link.from.0, link.to.0);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AreaGraph;
+    use crate::area::{Area, AreaIdentifier, GameAreas, Passage, PassageDestination};
+    use bevy::prelude::*;
+
+    const AREAS: &str = include_str!("../examples/areas.ron");
+
+    #[test]
+    fn graph_flags_one_way_links_and_dead_ends() {
+        let graph = AreaGraph::new(&GameAreas::from_ron(AREAS).unwrap());
+        assert_eq!(graph.areas.len(), 4);
+        let dead_ends: Vec<_> = graph.dead_ends().map(|node| node.area).collect();
+        assert_eq!(dead_ends, vec![AreaIdentifier(0), AreaIdentifier(3)]);
+        let one_way: Vec<_> = graph
+            .one_way_links()
+            .map(|link| (link.from, link.to))
+            .collect();
+        assert_eq!(one_way, vec![(AreaIdentifier(2), AreaIdentifier(3))]);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("0 [label=\"Village\", color=orange];"));
+        assert!(dot.contains("2 [label=\"Cave\"];"));
+        assert!(dot.contains("3 [label=\"Pit\", color=orange];"));
+        assert!(dot.contains("1 -> 2 [label=\"level 3\"];"));
+        assert!(dot.contains("2 -> 3 [style=dashed, color=red];"));
+
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+        assert_eq!(json["links"].as_array().unwrap().len(), 5);
+        assert_eq!(json["areas"][3]["dead_end"], true);
+    }
+
+    #[test]
+    fn passages_to_missing_areas_are_dangling() {
+        let passage = |destination: usize| {
+            Passage::new(
+                Transform::default(),
+                Sprite {
+                    custom_size: Some(Vec2::new(30., 80.)),
+                    ..default()
+                },
+                PassageDestination(destination.into(), Transform::default()),
+            )
+        };
+        let graph = AreaGraph::new(&GameAreas::new(vec![
+            Area::new(Color::BLACK, vec![passage(1), passage(5)]),
+            Area::new(Color::BLACK, vec![passage(0)]),
+        ]));
+        let dangling: Vec<_> = graph
+            .dangling_links()
+            .map(|link| (link.from, link.to))
+            .collect();
+        assert_eq!(dangling, vec![(AreaIdentifier(0), AreaIdentifier(5))]);
+        assert_eq!(graph.one_way_links().count(), 0);
+        assert!(graph.areas[0].dead_end);
+        assert!(graph
+            .to_dot()
+            .contains("0 -> 5 [style=bold, color=purple];"));
+    }
+}
@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+
+use crate::area::{AreaEntered, GameAreas};
+use crate::combat::Health;
+use crate::inventory::{Inventory, InventoryChanged};
+use crate::item::ItemDatabase;
+use crate::player::Player;
+use crate::progression::{Experience, Level, LevelCurve};
+use crate::wallet::Wallet;
+
+pub struct HudPlugin;
+
+/// Number of leading inventory slots shown on the HUD.
+pub const QUICK_SLOTS: usize = 4;
+
+/// Seconds the area name stays on screen after entering an area.
+const BANNER_SECONDS: f32 = 2.5;
+
+#[derive(Component)]
+struct HealthBar;
+
+/// Progress towards the next level.
+#[derive(Component)]
+struct ExperienceBar;
+
+#[derive(Component)]
+struct CurrencyText;
+
+#[derive(Component)]
+struct QuickSlotsText;
+
+#[derive(Component)]
+struct AreaBanner(Timer);
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AreaEntered>()
+            .add_event::<InventoryChanged>()
+            .add_systems(Startup, spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_bar,
+                    update_experience_bar,
+                    update_currency,
+                    update_quick_slots,
+                    show_area_banner,
+                    hide_area_banner,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_bar(parent: &mut ChildBuilder, color: Color, marker: impl Component) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(200.),
+                height: Val::Px(14.),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.6).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                })
+                .insert(marker);
+        });
+}
+
+fn hud_text(value: &str) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: 18.,
+            ..default()
+        },
+    )
+}
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(16.),
+                top: Val::Px(16.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_bar(parent, Color::srgb(0.8, 0.1, 0.1), HealthBar);
+            spawn_bar(parent, Color::srgb(0.2, 0.4, 0.9), ExperienceBar);
+            parent.spawn(hud_text("")).insert(CurrencyText);
+        });
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(16.),
+                bottom: Val::Px(16.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(hud_text("")).insert(QuickSlotsText);
+        });
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                top: Val::Px(80.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 40.,
+                            ..default()
+                        },
+                    )
+                })
+                .insert(AreaBanner(Timer::from_seconds(
+                    BANNER_SECONDS,
+                    TimerMode::Once,
+                )));
+        });
+}
+
+fn fill(fraction: f32) -> Val {
+    Val::Percent(fraction.clamp(0., 1.) * 100.)
+}
+
+fn update_health_bar(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut bar_query: Query<&mut Style, With<HealthBar>>,
+) {
+    let Ok(health) = player_query.get_single() else {
+        return;
+    };
+    for mut style in bar_query.iter_mut() {
+        style.width = fill(health.current / health.max);
+    }
+}
+
+type ProgressionChanged = Or<(Changed<Experience>, Changed<Level>)>;
+
+fn update_experience_bar(
+    player_query: Query<(&Experience, &Level), (With<Player>, ProgressionChanged)>,
+    curve: Option<Res<LevelCurve>>,
+    mut bar_query: Query<&mut Style, With<ExperienceBar>>,
+) {
+    let Ok((experience, level)) = player_query.get_single() else {
+        return;
+    };
+    let curve = curve.map(|curve| curve.clone()).unwrap_or_default();
+    let current = curve.experience_for_level(level.0).unwrap_or(0);
+    // The bar stays full at the level cap.
+    let fraction = match curve.experience_for_level(level.0 + 1) {
+        Some(next) if next > current => {
+            experience.0.saturating_sub(current) as f32 / (next - current) as f32
+        }
+        _ => 1.,
+    };
+    for mut style in bar_query.iter_mut() {
+        style.width = fill(fraction);
+    }
+}
+
+fn update_currency(
+    player_query: Query<&Wallet, (With<Player>, Changed<Wallet>)>,
+    mut text_query: Query<&mut Text, With<CurrencyText>>,
+) {
+    let Ok(wallet) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Gold: {}", wallet.0);
+    }
+}
+
+fn update_quick_slots(
+    mut ev_inventory_changed: EventReader<InventoryChanged>,
+    player_query: Query<&Inventory, With<Player>>,
+    database: Option<Res<ItemDatabase>>,
+    mut text_query: Query<&mut Text, With<QuickSlotsText>>,
+) {
+    let Some(changed) = ev_inventory_changed
+        .read()
+        .find(|changed| player_query.contains(changed.entity))
+    else {
+        return;
+    };
+    let inventory = player_query.get(changed.entity).unwrap();
+    let slots: Vec<String> = inventory
+        .slots()
+        .iter()
+        .take(QUICK_SLOTS)
+        .enumerate()
+        .map(|(i, slot)| {
+            let name = slot.map(|stack| {
+                let name = database
+                    .as_ref()
+                    .and_then(|database| database.get(stack.item))
+                    .map_or("?", |definition| definition.name.as_str());
+                format!("{name} x{}", stack.count)
+            });
+            format!("[{}] {}", i + 1, name.unwrap_or_else(|| "-".to_string()))
+        })
+        .collect();
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = slots.join("  ");
+    }
+}
+
+fn show_area_banner(
+    mut ev_area_entered: EventReader<AreaEntered>,
+    game_areas: Option<Res<GameAreas>>,
+    mut banner_query: Query<(&mut Text, &mut Visibility, &mut AreaBanner)>,
+) {
+    let Some(entered) = ev_area_entered.read().last() else {
+        return;
+    };
+    let Some(name) = game_areas
+        .as_ref()
+        .and_then(|areas| areas.get(entered.0))
+        .and_then(|area| area.name())
+    else {
+        return;
+    };
+    for (mut text, mut visibility, mut banner) in banner_query.iter_mut() {
+        text.sections[0].value = name.to_string();
+        *visibility = Visibility::Visible;
+        banner.0.reset();
+    }
+}
+
+fn hide_area_banner(time: Res<Time>, mut banner_query: Query<(&mut Visibility, &mut AreaBanner)>) {
+    for (mut visibility, mut banner) in banner_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        if banner.0.tick(time.delta()).just_finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AreaBanner, HealthBar, HudPlugin, QuickSlotsText};
+    use crate::area::{Area, AreaEntered, GameAreas};
+    use crate::combat::Health;
+    use crate::inventory::{Inventory, InventoryChanged};
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition};
+    use crate::player::Player;
+    use crate::progression::{Experience, Level};
+    use crate::wallet::Wallet;
+    use bevy::prelude::*;
+
+    #[test]
+    fn hud_follows_player_and_area() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(GameAreas::new(vec![
+            Area::new(Color::BLACK, vec![]).with_name("Village")
+        ]));
+        app.insert_resource(ItemDatabase::new(vec![ItemDefinition {
+            category: ItemCategory::Consumable,
+            ..material(0.into(), "Potion")
+        }]));
+        app.add_plugins(HudPlugin);
+        let mut inventory = Inventory::new(2);
+        inventory
+            .add(app.world().resource::<ItemDatabase>(), 0.into(), 3)
+            .unwrap();
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(Health::new(80.))
+            .insert(Experience(0))
+            .insert(Level(1))
+            .insert(Wallet(5))
+            .insert(inventory)
+            .id();
+        app.update();
+
+        app.world_mut().get_mut::<Health>(player).unwrap().current = 20.;
+        app.world_mut()
+            .send_event(InventoryChanged { entity: player });
+        app.world_mut().send_event(AreaEntered(0.into()));
+        app.update();
+        let width = app
+            .world_mut()
+            .query_filtered::<&Style, With<HealthBar>>()
+            .single(app.world())
+            .width;
+        assert_eq!(width, Val::Percent(25.));
+        let quick_slots = app
+            .world_mut()
+            .query_filtered::<&Text, With<QuickSlotsText>>()
+            .single(app.world())
+            .sections[0]
+            .value
+            .clone();
+        assert_eq!(quick_slots, "[1] Potion x3  [2] -");
+        let (banner, visibility) = app
+            .world_mut()
+            .query_filtered::<(&Text, &Visibility), With<AreaBanner>>()
+            .single(app.world());
+        assert_eq!(banner.sections[0].value, "Village");
+        assert_eq!(visibility, Visibility::Visible);
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+
+use crate::area::{AreaIdentifier, CurrentArea};
+use crate::player::{Facing, Player};
+use crate::state::is_playing;
+
+pub struct InteractionPlugin;
+
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct Interactable {
+    pub prompt: String,
+    /// Distance from the player at which the entity can be interacted with.
+    pub radius: f32,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>, radius: f32) -> Self {
+        Interactable {
+            prompt: prompt.into(),
+            radius,
+        }
+    }
+}
+
+/// Nearest interactable in front of the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Resource)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct Interacted {
+    pub entity: Entity,
+}
+
+#[derive(Component)]
+struct InteractionPrompt;
+
+const INTERACT_KEY: KeyCode = KeyCode::KeyE;
+const PROMPT_OFFSET: f32 = 50.;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InteractionTarget>()
+            .add_event::<Interacted>()
+            .add_systems(Startup, spawn_prompt)
+            .add_systems(
+                Update,
+                (find_interaction_target, interact, update_prompt)
+                    .chain()
+                    .run_if(is_playing),
+            );
+    }
+}
+
+fn spawn_prompt(mut commands: Commands) {
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.,
+                    ..default()
+                },
+            ),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(InteractionPrompt);
+}
+
+fn find_interaction_target(
+    current_area: Option<Res<CurrentArea>>,
+    player_query: Query<(&Transform, &Facing), With<Player>>,
+    interactable_query: Query<(Entity, &Transform, &Interactable, Option<&AreaIdentifier>)>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    let Ok((player_transform, facing)) = player_query.get_single() else {
+        target.0 = None;
+        return;
+    };
+    let origin = player_transform.translation.truncate();
+    let nearest = interactable_query
+        .iter()
+        .filter(|(_, _, _, area)| match (area, &current_area) {
+            (Some(area), Some(current_area)) => **area == current_area.0,
+            _ => true,
+        })
+        .filter_map(|(entity, transform, interactable, _)| {
+            let offset = transform.translation.truncate() - origin;
+            let distance = offset.length();
+            (distance <= interactable.radius && offset.dot(facing.0) >= 0.)
+                .then_some((entity, distance))
+        })
+        .min_by(|(_, l), (_, r)| l.total_cmp(r))
+        .map(|(entity, _)| entity);
+    if target.0 != nearest {
+        target.0 = nearest;
+    }
+}
+
+fn interact(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    target: Res<InteractionTarget>,
+    mut ev_interacted: EventWriter<Interacted>,
+) {
+    if !keyboard_input.just_pressed(INTERACT_KEY) {
+        return;
+    }
+    if let Some(entity) = target.0 {
+        ev_interacted.send(Interacted { entity });
+    }
+}
+
+fn update_prompt(
+    target: Res<InteractionTarget>,
+    interactable_query: Query<(&Transform, &Interactable), Without<InteractionPrompt>>,
+    mut prompt_query: Query<(&mut Transform, &mut Text, &mut Visibility), With<InteractionPrompt>>,
+) {
+    for (mut transform, mut text, mut visibility) in prompt_query.iter_mut() {
+        let Some((target_transform, interactable)) = target
+            .0
+            .and_then(|entity| interactable_query.get(entity).ok())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        transform.translation = target_transform.translation + Vec3::new(0., PROMPT_OFFSET, 10.);
+        text.sections[0].value = format!("[E] {}", interactable.prompt);
+        *visibility = Visibility::Visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interactable, Interacted, InteractionPlugin, InteractionTarget, INTERACT_KEY};
+    use crate::area::{AreaIdentifier, CurrentArea};
+    use crate::player::{Facing, Player};
+    use bevy::prelude::*;
+
+    #[test]
+    fn interacts_with_nearest_in_front() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(CurrentArea(0.into()));
+        app.add_plugins(InteractionPlugin);
+        app.world_mut()
+            .spawn(Player::default())
+            .insert(Transform::default())
+            .insert(Facing(Vec2::X));
+        let behind = app
+            .world_mut()
+            .spawn(Interactable::new("Talk", 100.))
+            .insert(Transform::from_xyz(-20., 0., 0.))
+            .id();
+        let far = app
+            .world_mut()
+            .spawn(Interactable::new("Talk", 100.))
+            .insert(Transform::from_xyz(80., 0., 0.))
+            .id();
+        let near = app
+            .world_mut()
+            .spawn(Interactable::new("Open", 100.))
+            .insert(Transform::from_xyz(40., 10., 0.))
+            .insert(AreaIdentifier(0))
+            .id();
+        app.world_mut()
+            .spawn(Interactable::new("Open", 100.))
+            .insert(Transform::from_xyz(30., 0., 0.))
+            .insert(AreaIdentifier(1));
+        app.update();
+        assert_eq!(app.world().resource::<InteractionTarget>().0, Some(near));
+
+        app.world_mut().entity_mut(near).despawn();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(INTERACT_KEY);
+        app.update();
+        let events = app.world().resource::<Events<Interacted>>();
+        let interacted: Vec<_> = events.get_reader().read(events).copied().collect();
+        assert_eq!(interacted, vec![Interacted { entity: far }]);
+
+        app.world_mut()
+            .query::<&mut Facing>()
+            .single_mut(app.world_mut())
+            .0 = Vec2::NEG_X;
+        app.update();
+        assert_eq!(app.world().resource::<InteractionTarget>().0, Some(behind));
+    }
+}
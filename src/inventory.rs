@@ -0,0 +1,343 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::item::{ItemDatabase, ItemId};
+
+pub struct InventoryPlugin;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InventoryError {
+    UnknownItem(ItemId),
+    Full,
+    NotEnoughItems,
+    InvalidSlot(usize),
+    EmptySlot(usize),
+    ItemMismatch,
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::UnknownItem(item) => write!(f, "unknown item {}", item.0),
+            InventoryError::Full => write!(f, "inventory is full"),
+            InventoryError::NotEnoughItems => write!(f, "not enough items"),
+            InventoryError::InvalidSlot(slot) => write!(f, "slot {slot} does not exist"),
+            InventoryError::EmptySlot(slot) => write!(f, "slot {slot} is empty"),
+            InventoryError::ItemMismatch => write!(f, "slots hold different items"),
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+/// Fixed number of slots, each holding a stack of up to the item's stack size.
+#[derive(Clone, Component, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+}
+
+/// Sent whenever an entity's inventory has been modified.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct InventoryChanged {
+    pub entity: Entity,
+}
+
+impl Inventory {
+    pub fn new(capacity: usize) -> Self {
+        Inventory {
+            slots: vec![None; capacity],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn count(&self, item: ItemId) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Adds all of the items, topping up existing stacks first, or nothing if they don't fit.
+    pub fn add(
+        &mut self,
+        database: &ItemDatabase,
+        item: ItemId,
+        count: u32,
+    ) -> Result<(), InventoryError> {
+        let stack_size = database
+            .get(item)
+            .ok_or(InventoryError::UnknownItem(item))?
+            .stack_size;
+        let room: u32 = self
+            .slots
+            .iter()
+            .map(|slot| match slot {
+                Some(stack) if stack.item == item => stack_size.saturating_sub(stack.count),
+                Some(_) => 0,
+                None => stack_size,
+            })
+            .sum();
+        if room < count {
+            return Err(InventoryError::Full);
+        }
+        let mut remaining = count;
+        for stack in self.slots.iter_mut().flatten() {
+            if stack.item == item {
+                let added = remaining.min(stack_size.saturating_sub(stack.count));
+                stack.count += added;
+                remaining -= added;
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if remaining == 0 {
+                break;
+            }
+            let added = remaining.min(stack_size);
+            *slot = Some(ItemStack { item, count: added });
+            remaining -= added;
+        }
+        Ok(())
+    }
+
+    /// Removes the items, taking from the last stacks first, or nothing if there aren't enough.
+    pub fn remove(&mut self, item: ItemId, count: u32) -> Result<(), InventoryError> {
+        if self.count(item) < count {
+            return Err(InventoryError::NotEnoughItems);
+        }
+        let mut remaining = count;
+        for slot in self.slots.iter_mut().rev() {
+            if remaining == 0 {
+                break;
+            }
+            if let Some(stack) = slot.as_mut().filter(|stack| stack.item == item) {
+                let removed = remaining.min(stack.count);
+                stack.count -= removed;
+                remaining -= removed;
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves `count` items from a slot into a new stack in the first empty slot, returning that
+    /// slot.
+    pub fn split(&mut self, slot: usize, count: u32) -> Result<usize, InventoryError> {
+        let stack = self
+            .slots
+            .get(slot)
+            .ok_or(InventoryError::InvalidSlot(slot))?
+            .ok_or(InventoryError::EmptySlot(slot))?;
+        if count == 0 || count >= stack.count {
+            return Err(InventoryError::NotEnoughItems);
+        }
+        let target = self
+            .slots
+            .iter()
+            .position(Option::is_none)
+            .ok_or(InventoryError::Full)?;
+        self.slots[slot] = Some(ItemStack {
+            item: stack.item,
+            count: stack.count - count,
+        });
+        self.slots[target] = Some(ItemStack {
+            item: stack.item,
+            count,
+        });
+        Ok(target)
+    }
+
+    /// Moves as many items as fit from one stack onto another stack of the same item, or into
+    /// the target slot if it is empty.
+    pub fn stack(
+        &mut self,
+        database: &ItemDatabase,
+        from: usize,
+        to: usize,
+    ) -> Result<(), InventoryError> {
+        let source = self
+            .slots
+            .get(from)
+            .ok_or(InventoryError::InvalidSlot(from))?
+            .ok_or(InventoryError::EmptySlot(from))?;
+        let target = *self.slots.get(to).ok_or(InventoryError::InvalidSlot(to))?;
+        if from == to {
+            return Ok(());
+        }
+        let stack_size = database
+            .get(source.item)
+            .ok_or(InventoryError::UnknownItem(source.item))?
+            .stack_size;
+        let target_count = match target {
+            Some(target) if target.item != source.item => return Err(InventoryError::ItemMismatch),
+            Some(target) => target.count,
+            None => 0,
+        };
+        let moved = source.count.min(stack_size.saturating_sub(target_count));
+        self.slots[to] = Some(ItemStack {
+            item: source.item,
+            count: target_count + moved,
+        });
+        self.slots[from] = Some(ItemStack {
+            item: source.item,
+            count: source.count - moved,
+        })
+        .filter(|stack| stack.count > 0);
+        Ok(())
+    }
+}
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemDatabase>()
+            .add_event::<InventoryChanged>()
+            .add_systems(PostUpdate, inventory_changed);
+    }
+}
+
+fn inventory_changed(
+    inventory_query: Query<Entity, Changed<Inventory>>,
+    mut ev_inventory_changed: EventWriter<InventoryChanged>,
+) {
+    for entity in inventory_query.iter() {
+        ev_inventory_changed.send(InventoryChanged { entity });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Inventory, InventoryError, ItemStack};
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition, ItemId};
+
+    const POTION: ItemId = ItemId(0);
+    const SWORD: ItemId = ItemId(1);
+
+    fn get_test_database() -> ItemDatabase {
+        ItemDatabase::new(vec![
+            ItemDefinition {
+                stack_size: 5,
+                category: ItemCategory::Consumable,
+                ..material(POTION, "Potion")
+            },
+            ItemDefinition {
+                stack_size: 1,
+                category: ItemCategory::Equipment,
+                ..material(SWORD, "Sword")
+            },
+        ])
+    }
+
+    #[test]
+    fn add_fills_stacks_then_empty_slots() {
+        let database = get_test_database();
+        let mut inventory = Inventory::new(3);
+        inventory.add(&database, POTION, 3).unwrap();
+        inventory.add(&database, SWORD, 1).unwrap();
+        inventory.add(&database, POTION, 4).unwrap();
+        assert_eq!(
+            inventory.slots(),
+            &[
+                Some(ItemStack {
+                    item: POTION,
+                    count: 5
+                }),
+                Some(ItemStack {
+                    item: SWORD,
+                    count: 1
+                }),
+                Some(ItemStack {
+                    item: POTION,
+                    count: 2
+                }),
+            ]
+        );
+        assert_eq!(inventory.count(POTION), 7);
+    }
+
+    #[test]
+    fn add_respects_capacity() {
+        let database = get_test_database();
+        let mut inventory = Inventory::new(2);
+        inventory.add(&database, SWORD, 1).unwrap();
+        assert_eq!(
+            inventory.add(&database, POTION, 6),
+            Err(InventoryError::Full)
+        );
+        assert_eq!(inventory.count(POTION), 0);
+        assert_eq!(
+            inventory.add(&database, ItemId(9), 1),
+            Err(InventoryError::UnknownItem(ItemId(9)))
+        );
+    }
+
+    #[test]
+    fn remove_takes_from_last_stacks() {
+        let database = get_test_database();
+        let mut inventory = Inventory::new(3);
+        inventory.add(&database, POTION, 7).unwrap();
+        assert_eq!(
+            inventory.remove(POTION, 8),
+            Err(InventoryError::NotEnoughItems)
+        );
+        inventory.remove(POTION, 3).unwrap();
+        assert_eq!(
+            inventory.slots(),
+            &[
+                Some(ItemStack {
+                    item: POTION,
+                    count: 4
+                }),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn split_and_stack() {
+        let database = get_test_database();
+        let mut inventory = Inventory::new(3);
+        inventory.add(&database, POTION, 5).unwrap();
+        assert_eq!(inventory.split(0, 2), Ok(1));
+        assert_eq!(inventory.count(POTION), 5);
+        assert_eq!(inventory.split(0, 3), Err(InventoryError::NotEnoughItems));
+        assert_eq!(inventory.split(2, 1), Err(InventoryError::EmptySlot(2)));
+
+        inventory.stack(&database, 1, 0).unwrap();
+        assert_eq!(
+            inventory.slots(),
+            &[
+                Some(ItemStack {
+                    item: POTION,
+                    count: 5
+                }),
+                None,
+                None
+            ]
+        );
+        inventory.add(&database, SWORD, 1).unwrap();
+        assert_eq!(
+            inventory.stack(&database, 1, 0),
+            Err(InventoryError::ItemMismatch)
+        );
+    }
+}
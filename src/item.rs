@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::equipment::Equippable;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ItemId(pub usize);
+
+impl From<usize> for ItemId {
+    fn from(val: usize) -> Self {
+        ItemId(val)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ItemCategory {
+    Consumable,
+    Equipment,
+    Material,
+    Key,
+    Misc,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum UseEffect {
+    Heal(f32),
+    /// Increases move speed by a fraction for a number of seconds.
+    SpeedBoost {
+        percent: f32,
+        duration: f32,
+    },
+    /// Takes the user back to their [`ReturnPoint`](crate::consumable::ReturnPoint).
+    ReturnScroll,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ItemDefinition {
+    pub id: ItemId,
+    pub name: String,
+    /// Asset path of the item's icon.
+    pub icon: Option<String>,
+    pub stack_size: u32,
+    pub category: ItemCategory,
+    pub use_effect: Option<UseEffect>,
+    /// Seconds before the item can be used again.
+    #[serde(default)]
+    pub use_cooldown: f32,
+    pub equipment: Option<Equippable>,
+}
+
+#[derive(Clone, Debug, Default, Resource)]
+pub struct ItemDatabase {
+    items: HashMap<ItemId, ItemDefinition>,
+}
+
+impl ItemDatabase {
+    pub fn new(items: Vec<ItemDefinition>) -> Self {
+        ItemDatabase {
+            items: items.into_iter().map(|item| (item.id, item)).collect(),
+        }
+    }
+
+    pub fn get(&self, id: ItemId) -> Option<&ItemDefinition> {
+        self.items.get(&id)
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use super::{ItemCategory, ItemDatabase, ItemDefinition, ItemId};
+
+    /// Plain item that stacks to 10 and can't be used or equipped. Tests override the fields
+    /// they care about with struct update syntax.
+    pub fn material(id: ItemId, name: &str) -> ItemDefinition {
+        ItemDefinition {
+            id,
+            name: name.to_string(),
+            icon: None,
+            stack_size: 10,
+            category: ItemCategory::Material,
+            use_effect: None,
+            use_cooldown: 0.,
+            equipment: None,
+        }
+    }
+
+    /// Herb, water and potion materials with ids 0, 1 and 2.
+    pub fn test_database() -> ItemDatabase {
+        ItemDatabase::new(vec![
+            material(ItemId(0), "Herb"),
+            material(ItemId(1), "Water"),
+            material(ItemId(2), "Potion"),
+        ])
+    }
+}
@@ -1,4 +1,34 @@
 pub mod area;
+pub mod autosave;
+pub mod battle;
+pub mod clock;
+pub mod combat;
+pub mod consumable;
+pub mod crafting;
+pub mod dialogue;
+pub mod encounter;
 pub mod enemy;
+pub mod equipment;
+pub mod flags;
+pub mod graph;
+pub mod hud;
+pub mod interaction;
+pub mod inventory;
+pub mod item;
+pub mod loot;
+pub mod map;
+pub mod menu;
+pub mod migration;
+pub mod npc;
 pub mod physics;
+pub mod pickup;
 pub mod player;
+pub mod progression;
+pub mod projectile;
+pub mod quest;
+pub mod rng;
+pub mod save;
+pub mod shop;
+pub mod state;
+pub mod stats;
+pub mod wallet;
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::area::{AreaIdentifier, CurrentArea};
+use crate::combat::{apply_damage, Defeated};
+use crate::item::ItemId;
+use crate::pickup::{Pickup, PickupBundle};
+use crate::rng::GameRng;
+
+pub struct LootPlugin;
+
+const DROP_SIZE: f32 = 16.;
+const DROP_SPREAD: f32 = 24.;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LootDrop {
+    Item {
+        item: ItemId,
+        count: u32,
+    },
+    /// Amount picked uniformly from the inclusive range. A `max` below `min` always gives `min`.
+    Currency {
+        min: u32,
+        max: u32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LootEntry {
+    Guaranteed(LootDrop),
+    Chance {
+        chance: f32,
+        drop: LootDrop,
+    },
+    /// Picks `rolls` nested tables by weight and rolls each of them.
+    Weighted {
+        rolls: u32,
+        options: Vec<(u32, LootTable)>,
+    },
+}
+
+/// Dropped as pickups where the entity is defeated.
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub struct LootTable(pub Vec<LootEntry>);
+
+impl LootDrop {
+    fn roll(&self, rng: &mut impl Rng) -> Pickup {
+        match *self {
+            LootDrop::Item { item, count } => Pickup::Item { item, count },
+            LootDrop::Currency { min, max } => Pickup::Currency(rng.gen_range(min..=max.max(min))),
+        }
+    }
+}
+
+impl LootTable {
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<Pickup> {
+        let mut drops = Vec::new();
+        for entry in &self.0 {
+            match entry {
+                LootEntry::Guaranteed(drop) => drops.push(drop.roll(rng)),
+                LootEntry::Chance { chance, drop } => {
+                    if rng.gen::<f32>() < *chance {
+                        drops.push(drop.roll(rng));
+                    }
+                }
+                LootEntry::Weighted { rolls, options } => {
+                    let total: u32 = options.iter().map(|(weight, _)| weight).sum();
+                    if total == 0 {
+                        continue;
+                    }
+                    for _ in 0..*rolls {
+                        let mut pick = rng.gen_range(0..total);
+                        for (weight, table) in options {
+                            if pick < *weight {
+                                drops.extend(table.roll(rng));
+                                break;
+                            }
+                            pick -= weight;
+                        }
+                    }
+                }
+            }
+        }
+        drops
+    }
+}
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .add_systems(Update, drop_loot.after(apply_damage));
+    }
+}
+
+fn drop_loot(
+    mut commands: Commands,
+    mut ev_defeated: EventReader<Defeated>,
+    loot_query: Query<(&LootTable, &Transform)>,
+    current_area: Option<Res<CurrentArea>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let area = current_area.map_or(AreaIdentifier(0), |current_area| current_area.0);
+    for defeated in ev_defeated.read() {
+        let Ok((table, transform)) = loot_query.get(defeated.entity) else {
+            continue;
+        };
+        for pickup in table.roll(&mut rng.0) {
+            let offset = Vec2::new(
+                rng.0.gen_range(-DROP_SPREAD..=DROP_SPREAD),
+                rng.0.gen_range(-DROP_SPREAD..=DROP_SPREAD),
+            );
+            let color = match pickup {
+                Pickup::Item { .. } => Color::srgb(0.9, 0.9, 0.9),
+                Pickup::Currency(_) => Color::srgb(1., 0.84, 0.),
+            };
+            commands
+                .spawn(PickupBundle::new(
+                    Transform::from_translation(transform.translation + offset.extend(0.)),
+                    Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(DROP_SIZE)),
+                        ..default()
+                    },
+                    pickup,
+                ))
+                .insert(area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LootDrop, LootEntry, LootTable};
+    use crate::area::{AreaIdentifier, CurrentArea};
+    use crate::combat::Defeated;
+    use crate::item::ItemId;
+    use crate::pickup::Pickup;
+    use crate::rng::GameRng;
+    use bevy::prelude::*;
+
+    fn get_test_table() -> LootTable {
+        LootTable(vec![
+            LootEntry::Guaranteed(LootDrop::Item {
+                item: ItemId(0),
+                count: 1,
+            }),
+            LootEntry::Chance {
+                chance: 0.5,
+                drop: LootDrop::Currency { min: 5, max: 10 },
+            },
+            LootEntry::Weighted {
+                rolls: 2,
+                options: vec![
+                    (
+                        1,
+                        LootTable(vec![LootEntry::Guaranteed(LootDrop::Item {
+                            item: ItemId(1),
+                            count: 1,
+                        })]),
+                    ),
+                    (1, LootTable::default()),
+                ],
+            },
+        ])
+    }
+
+    #[test]
+    fn rolls_are_reproducible() {
+        let table = get_test_table();
+        let roll = |seed| {
+            let mut rng = GameRng::seeded(seed);
+            (0..50).map(|_| table.roll(&mut rng.0)).collect::<Vec<_>>()
+        };
+        let rolls = roll(3);
+        assert_eq!(rolls, roll(3));
+        for drops in &rolls {
+            assert_eq!(
+                drops[0],
+                Pickup::Item {
+                    item: ItemId(0),
+                    count: 1
+                }
+            );
+            assert!(drops.len() <= 4);
+            for drop in drops {
+                if let Pickup::Currency(amount) = drop {
+                    assert!((5..=10).contains(amount));
+                }
+            }
+        }
+        let has_currency =
+            |drops: &Vec<Pickup>| drops.iter().any(|drop| matches!(drop, Pickup::Currency(_)));
+        assert!(rolls.iter().any(has_currency));
+        assert!(!rolls.iter().all(has_currency));
+
+        let inverted = LootDrop::Currency { min: 7, max: 2 };
+        assert_eq!(
+            inverted.roll(&mut GameRng::seeded(0).0),
+            Pickup::Currency(7)
+        );
+    }
+
+    #[test]
+    fn defeated_entities_drop_area_scoped_pickups() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<Defeated>();
+        app.insert_resource(GameRng::seeded(0));
+        app.insert_resource(CurrentArea(2.into()));
+        app.add_plugins(super::LootPlugin);
+        let enemy = app
+            .world_mut()
+            .spawn(LootTable(vec![LootEntry::Guaranteed(LootDrop::Currency {
+                min: 3,
+                max: 3,
+            })]))
+            .insert(Transform::from_xyz(100., 50., 0.))
+            .id();
+        app.world_mut().send_event(Defeated { entity: enemy });
+        app.update();
+
+        let mut pickup_query = app
+            .world_mut()
+            .query::<(&Pickup, &Transform, &AreaIdentifier)>();
+        let pickups: Vec<_> = pickup_query.iter(app.world()).collect();
+        assert_eq!(pickups.len(), 1);
+        let (pickup, transform, area) = pickups[0];
+        assert_eq!(pickup, &Pickup::Currency(3));
+        assert!(transform.translation.distance(Vec3::new(100., 50., 0.)) < 50.);
+        assert_eq!(area, &AreaIdentifier(2));
+    }
+}
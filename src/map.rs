@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::area::{AreaEntered, AreaIdentifier, CurrentArea, GameAreas};
+use crate::player::Player;
+use crate::state::is_playing;
+
+pub struct MapPlugin;
+
+#[derive(Clone, Debug, Resource)]
+pub struct MapSettings {
+    /// World space covered by an area, centered on the origin.
+    pub area_size: Vec2,
+    pub minimap_size: Vec2,
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        MapSettings {
+            area_size: Vec2::new(1280., 720.),
+            minimap_size: Vec2::new(192., 108.),
+        }
+    }
+}
+
+/// Areas the player has entered. Only these are revealed on the world map.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Resource, Serialize)]
+pub struct VisitedAreas(pub HashSet<AreaIdentifier>);
+
+/// Grid cell of every area on the world map. Areas are placed next to each other in the direction
+/// of the passage linking them, starting from the first area.
+pub fn area_layout(game_areas: &GameAreas) -> HashMap<AreaIdentifier, IVec2> {
+    let mut layout: HashMap<AreaIdentifier, IVec2> = HashMap::new();
+    for (start, _) in game_areas.iter() {
+        if layout.contains_key(&start) {
+            continue;
+        }
+        // Disconnected groups of areas are laid out to the right of the previous ones.
+        let column = layout.values().map(|cell| cell.x + 2).max().unwrap_or(0);
+        layout.insert(start, IVec2::new(column, 0));
+        let mut queue = VecDeque::from([start]);
+        while let Some(identifier) = queue.pop_front() {
+            let Some(area) = game_areas.get(identifier) else {
+                continue;
+            };
+            for passage in area.passages() {
+                let destination = passage.destination().0;
+                if layout.contains_key(&destination) || game_areas.get(destination).is_none() {
+                    continue;
+                }
+                let offset = passage.transform().translation.truncate();
+                let step = if offset.x.abs() >= offset.y.abs() {
+                    IVec2::new(if offset.x < 0. { -1 } else { 1 }, 0)
+                } else {
+                    IVec2::new(0, if offset.y < 0. { -1 } else { 1 })
+                };
+                let mut cell = layout[&identifier] + step;
+                while layout.values().any(|other| *other == cell) {
+                    cell += step;
+                }
+                layout.insert(destination, cell);
+                queue.push_back(destination);
+            }
+        }
+    }
+    layout
+}
+
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct MinimapPassage;
+
+#[derive(Component)]
+struct MinimapPlayer;
+
+#[derive(Component)]
+struct WorldMapScreen;
+
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+struct WorldMapNode(AreaIdentifier);
+
+const MINIMAP_MARKER: f32 = 6.;
+const WORLD_MAP_CELL: f32 = 140.;
+const WORLD_MAP_NODE: Vec2 = Vec2::new(100., 40.);
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapSettings>()
+            .init_resource::<VisitedAreas>()
+            .add_event::<AreaEntered>()
+            .add_systems(Startup, spawn_minimap)
+            .add_systems(Update, (record_visited_areas, minimap_passages).chain())
+            .add_systems(Update, minimap_player)
+            .add_systems(Update, toggle_world_map.run_if(is_playing));
+    }
+}
+
+fn record_visited_areas(
+    mut ev_area_entered: EventReader<AreaEntered>,
+    mut visited: ResMut<VisitedAreas>,
+) {
+    for entered in ev_area_entered.read() {
+        visited.0.insert(entered.0);
+    }
+}
+
+fn minimap_position(settings: &MapSettings, translation: Vec3) -> (Val, Val) {
+    let relative = translation.truncate() / settings.area_size + Vec2::splat(0.5);
+    (
+        Val::Px(relative.x * settings.minimap_size.x - MINIMAP_MARKER / 2.),
+        Val::Px((1. - relative.y) * settings.minimap_size.y - MINIMAP_MARKER / 2.),
+    )
+}
+
+fn marker(settings: &MapSettings, translation: Vec3, color: Color) -> NodeBundle {
+    let (left, top) = minimap_position(settings, translation);
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left,
+            top,
+            width: Val::Px(MINIMAP_MARKER),
+            height: Val::Px(MINIMAP_MARKER),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    }
+}
+
+fn spawn_minimap(mut commands: Commands, settings: Res<MapSettings>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(16.),
+                top: Val::Px(16.),
+                width: Val::Px(settings.minimap_size.x),
+                height: Val::Px(settings.minimap_size.y),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.6).into(),
+            ..default()
+        })
+        .insert(Minimap)
+        .with_children(|parent| {
+            parent
+                .spawn(marker(&settings, Vec3::ZERO, Color::WHITE))
+                .insert(MinimapPlayer)
+                .insert(ZIndex::Local(1));
+        });
+}
+
+fn minimap_passages(
+    mut commands: Commands,
+    mut ev_area_entered: EventReader<AreaEntered>,
+    settings: Res<MapSettings>,
+    game_areas: Option<Res<GameAreas>>,
+    minimap_query: Query<Entity, With<Minimap>>,
+    passage_query: Query<Entity, With<MinimapPassage>>,
+) {
+    let Some(entered) = ev_area_entered.read().last() else {
+        return;
+    };
+    for passage in passage_query.iter() {
+        commands.entity(passage).despawn_recursive();
+    }
+    let Some(area) = game_areas.as_ref().and_then(|areas| areas.get(entered.0)) else {
+        return;
+    };
+    for minimap in minimap_query.iter() {
+        commands.entity(minimap).with_children(|parent| {
+            for passage in area.passages() {
+                parent
+                    .spawn(marker(
+                        &settings,
+                        passage.transform().translation,
+                        Color::srgb(0., 1., 0.),
+                    ))
+                    .insert(MinimapPassage);
+            }
+        });
+    }
+}
+
+fn minimap_player(
+    settings: Res<MapSettings>,
+    player_query: Query<&Transform, (With<Player>, Changed<Transform>)>,
+    mut marker_query: Query<&mut Style, With<MinimapPlayer>>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+    let (left, top) = minimap_position(&settings, transform.translation);
+    for mut style in marker_query.iter_mut() {
+        style.left = left;
+        style.top = top;
+    }
+}
+
+fn line(from: Vec2, to: Vec2) -> NodeBundle {
+    let min = from.min(to);
+    let size = (from - to).abs().max(Vec2::splat(2.));
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(min.x - 1.),
+            top: Val::Px(min.y - 1.),
+            width: Val::Px(size.x),
+            height: Val::Px(size.y),
+            ..default()
+        },
+        background_color: Color::srgb(0.6, 0.6, 0.6).into(),
+        ..default()
+    }
+}
+
+fn toggle_world_map(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    screen_query: Query<Entity, With<WorldMapScreen>>,
+    game_areas: Option<Res<GameAreas>>,
+    visited: Res<VisitedAreas>,
+    current_area: Option<Res<CurrentArea>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+    if !screen_query.is_empty() {
+        for screen in screen_query.iter() {
+            commands.entity(screen).despawn_recursive();
+        }
+        return;
+    }
+    let Some(game_areas) = game_areas else {
+        return;
+    };
+    let layout: HashMap<_, _> = area_layout(&game_areas)
+        .into_iter()
+        .filter(|(area, _)| visited.0.contains(area))
+        .collect();
+    let top_left = IVec2::new(
+        layout.values().map(|cell| cell.x).min().unwrap_or(0),
+        layout.values().map(|cell| cell.y).max().unwrap_or(0),
+    );
+    // Cell centers in UI space, where y grows downwards.
+    let center = |cell: IVec2| {
+        Vec2::new((cell.x - top_left.x) as f32, (top_left.y - cell.y) as f32) * WORLD_MAP_CELL
+            + Vec2::splat(WORLD_MAP_CELL / 2.)
+    };
+    let mut links = HashSet::new();
+    for &from in layout.keys() {
+        for passage in game_areas.get(from).unwrap().passages() {
+            let to = passage.destination().0;
+            if to != from && layout.contains_key(&to) {
+                links.insert((from.0.min(to.0), from.0.max(to.0)));
+            }
+        }
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(24.)),
+                ..default()
+            },
+            background_color: Color::srgba(0.05, 0.05, 0.15, 0.9).into(),
+            z_index: ZIndex::Global(5),
+            ..default()
+        })
+        .insert(WorldMapScreen)
+        .with_children(|parent| {
+            // Links are drawn as horizontal then vertical segments between area centers.
+            for (from, to) in links {
+                let from = center(layout[&AreaIdentifier(from)]);
+                let to = center(layout[&AreaIdentifier(to)]);
+                let corner = Vec2::new(to.x, from.y);
+                parent.spawn(line(from, corner));
+                parent.spawn(line(corner, to));
+            }
+            for (&area, &cell) in &layout {
+                let is_current = current_area
+                    .as_ref()
+                    .is_some_and(|current_area| current_area.0 == area);
+                let position = center(cell) - WORLD_MAP_NODE / 2.;
+                let name = game_areas
+                    .get(area)
+                    .and_then(|area| area.name())
+                    .map_or_else(|| format!("Area {}", area.0), str::to_string);
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(position.x),
+                            top: Val::Px(position.y),
+                            width: Val::Px(WORLD_MAP_NODE.x),
+                            height: Val::Px(WORLD_MAP_NODE.y),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: if is_current {
+                            Color::srgb(0.8, 0.6, 0.1).into()
+                        } else {
+                            Color::srgb(0.2, 0.2, 0.3).into()
+                        },
+                        ..default()
+                    })
+                    .insert(WorldMapNode(area))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            name,
+                            TextStyle {
+                                font_size: 16.,
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{area_layout, MapPlugin, VisitedAreas, WorldMapNode};
+    use crate::area::{Area, AreaEntered, AreaIdentifier, GameAreas, Passage, PassageDestination};
+    use bevy::prelude::*;
+
+    fn passage(x: f32, y: f32, destination: usize) -> Passage {
+        Passage::new(
+            Transform::from_xyz(x, y, 1.),
+            Sprite {
+                custom_size: Some(Vec2::new(30., 80.)),
+                ..default()
+            },
+            PassageDestination(destination.into(), Transform::default()),
+        )
+    }
+
+    fn get_test_areas() -> GameAreas {
+        GameAreas::new(vec![
+            Area::new(
+                Color::BLACK,
+                vec![passage(600., 0., 1), passage(0., 300., 2)],
+            ),
+            Area::new(Color::BLACK, vec![passage(-600., 0., 0)]),
+            Area::new(Color::BLACK, vec![passage(0., -300., 0)]),
+            Area::new(Color::BLACK, vec![]),
+        ])
+    }
+
+    #[test]
+    fn areas_are_laid_out_along_passages() {
+        let layout = area_layout(&get_test_areas());
+        assert_eq!(layout[&AreaIdentifier(0)], IVec2::new(0, 0));
+        assert_eq!(layout[&AreaIdentifier(1)], IVec2::new(1, 0));
+        assert_eq!(layout[&AreaIdentifier(2)], IVec2::new(0, 1));
+        assert_eq!(layout[&AreaIdentifier(3)], IVec2::new(3, 0));
+    }
+
+    #[test]
+    fn world_map_only_shows_visited_areas() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(get_test_areas());
+        app.add_plugins(MapPlugin);
+        app.world_mut().send_event(AreaEntered(0.into()));
+        app.update();
+        app.world_mut().send_event(AreaEntered(2.into()));
+        app.update();
+        assert_eq!(
+            app.world().resource::<VisitedAreas>(),
+            &VisitedAreas([AreaIdentifier(0), AreaIdentifier(2)].into())
+        );
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyM);
+        app.update();
+        let mut nodes: Vec<_> = app
+            .world_mut()
+            .query::<&WorldMapNode>()
+            .iter(app.world())
+            .map(|node| node.0)
+            .collect();
+        nodes.sort_by_key(|area| area.0);
+        assert_eq!(nodes, vec![AreaIdentifier(0), AreaIdentifier(2)]);
+    }
+}
@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+
+use crate::save::{LoadGame, SaveGame, SaveSettings, QUICK_SAVE_SLOT};
+use crate::shop::ActiveShop;
+use crate::state::GameState;
+
+/// Main, pause and game over menus. Requires [`GameStatePlugin`](crate::state::GameStatePlugin).
+pub struct MenuPlugin;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuAction {
+    NewGame,
+    /// Loads the quick save slot.
+    Continue,
+    Resume,
+    Save,
+    Quit,
+}
+
+impl MenuAction {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuAction::NewGame => "New game",
+            MenuAction::Continue => "Continue",
+            MenuAction::Resume => "Resume",
+            MenuAction::Save => "Save",
+            MenuAction::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Component)]
+struct MenuScreen(Vec<MenuAction>);
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGame>()
+            .add_event::<LoadGame>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_menu)
+            .add_systems(OnExit(GameState::GameOver), despawn_menu)
+            .add_systems(
+                Update,
+                toggle_pause.run_if(
+                    in_state(GameState::Playing)
+                        .or_else(in_state(GameState::Paused))
+                        .and_then(not(resource_exists::<ActiveShop>)),
+                ),
+            )
+            .add_systems(Update, menu_input.after(toggle_pause));
+    }
+}
+
+fn has_quick_save(settings: Option<Res<SaveSettings>>) -> bool {
+    settings.is_some_and(|settings| settings.slot_path(QUICK_SAVE_SLOT).exists())
+}
+
+fn spawn_main_menu(mut commands: Commands, settings: Option<Res<SaveSettings>>) {
+    let mut actions = vec![MenuAction::NewGame];
+    if has_quick_save(settings) {
+        actions.push(MenuAction::Continue);
+    }
+    actions.push(MenuAction::Quit);
+    spawn_menu(&mut commands, "RPG System 2D", actions);
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    let actions = vec![MenuAction::Resume, MenuAction::Save, MenuAction::Quit];
+    spawn_menu(&mut commands, "Paused", actions);
+}
+
+fn spawn_game_over_menu(mut commands: Commands, settings: Option<Res<SaveSettings>>) {
+    let mut actions = vec![];
+    if has_quick_save(settings) {
+        actions.push(MenuAction::Continue);
+    }
+    actions.push(MenuAction::Quit);
+    spawn_menu(&mut commands, "Game over", actions);
+}
+
+fn spawn_menu(commands: &mut Commands, title: &str, actions: Vec<MenuAction>) {
+    let mut lines = vec![title.to_string(), String::new()];
+    for (i, action) in actions.iter().enumerate() {
+        lines.push(format!("{}: {}", i + 1, action.label()));
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::srgba(0.05, 0.05, 0.15, 0.9).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(MenuScreen(actions))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                lines.join("\n"),
+                TextStyle {
+                    font_size: 28.,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_menu(mut commands: Commands, screen_query: Query<Entity, With<MenuScreen>>) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn toggle_pause(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    match state.get() {
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Playing),
+        _ => {}
+    }
+}
+
+const MENU_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+fn menu_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    screen_query: Query<&MenuScreen>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_save: EventWriter<SaveGame>,
+    mut ev_load: EventWriter<LoadGame>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    let Ok(screen) = screen_query.get_single() else {
+        return;
+    };
+    let Some(action) = MENU_KEYS
+        .iter()
+        .zip(&screen.0)
+        .find(|(key, _)| keyboard_input.just_pressed(**key))
+        .map(|(_, action)| *action)
+    else {
+        return;
+    };
+    match action {
+        MenuAction::NewGame => next_state.set(GameState::Loading),
+        MenuAction::Continue => {
+            ev_load.send(LoadGame {
+                slot: QUICK_SAVE_SLOT,
+            });
+            next_state.set(GameState::Loading);
+        }
+        MenuAction::Resume => next_state.set(GameState::Playing),
+        MenuAction::Save => {
+            ev_save.send(SaveGame {
+                slot: QUICK_SAVE_SLOT,
+            });
+        }
+        MenuAction::Quit => {
+            ev_exit.send(AppExit::Success);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MenuAction, MenuPlugin, MenuScreen};
+    use crate::state::{GameState, GameStatePlugin};
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::plugin::{NoUserData, RapierPhysicsPlugin};
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        app.update();
+        let mut keyboard_input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard_input.release(key);
+        keyboard_input.clear();
+        app.update();
+    }
+
+    fn menu(app: &mut App) -> Option<Vec<MenuAction>> {
+        app.world_mut()
+            .query::<&MenuScreen>()
+            .get_single(app.world())
+            .ok()
+            .map(|screen| screen.0.clone())
+    }
+
+    #[test]
+    fn menus_follow_game_state() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_plugins(GameStatePlugin);
+        app.add_plugins(MenuPlugin);
+        app.update();
+        assert_eq!(
+            menu(&mut app),
+            Some(vec![MenuAction::NewGame, MenuAction::Quit])
+        );
+
+        press(&mut app, KeyCode::Digit1);
+        app.update();
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Playing
+        );
+        assert_eq!(menu(&mut app), None);
+
+        press(&mut app, KeyCode::Escape);
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Paused
+        );
+        assert_eq!(
+            menu(&mut app),
+            Some(vec![MenuAction::Resume, MenuAction::Save, MenuAction::Quit])
+        );
+
+        press(&mut app, KeyCode::Escape);
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Playing
+        );
+        assert_eq!(menu(&mut app), None);
+    }
+}
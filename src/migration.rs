@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+/// Upgrades a document from one version to the next.
+pub type Migration = fn(&mut Value) -> Result<(), String>;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MigrationError {
+    MissingVersion,
+    /// The document is newer than the current version.
+    UnsupportedVersion(u32),
+    MissingMigration(u32),
+    Failed {
+        from: u32,
+        message: String,
+    },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::MissingVersion => write!(f, "document has no version"),
+            MigrationError::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {version}")
+            }
+            MigrationError::MissingMigration(from) => {
+                write!(f, "no migration from version {from}")
+            }
+            MigrationError::Failed { from, message } => {
+                write!(f, "migration from version {from} failed: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Registry of migrations for a persisted format that keeps its schema version in a top-level
+/// `version` field. Older documents are upgraded one version at a time.
+#[derive(Clone, Debug)]
+pub struct Migrations {
+    current: u32,
+    steps: BTreeMap<u32, Migration>,
+}
+
+impl Migrations {
+    pub fn new(current: u32) -> Self {
+        Migrations {
+            current,
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the migration from version `from` to `from + 1`.
+    pub fn with_migration(mut self, from: u32, migration: Migration) -> Self {
+        self.steps.insert(from, migration);
+        self
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Upgrades the document to the current version, returning the version it had before.
+    pub fn migrate(&self, document: &mut Value) -> Result<u32, MigrationError> {
+        let original = document
+            .get("version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or(MigrationError::MissingVersion)?;
+        if original > self.current {
+            return Err(MigrationError::UnsupportedVersion(original));
+        }
+        for from in original..self.current {
+            let migration = self
+                .steps
+                .get(&from)
+                .ok_or(MigrationError::MissingMigration(from))?;
+            migration(document).map_err(|message| MigrationError::Failed { from, message })?;
+            document["version"] = Value::from(from + 1);
+        }
+        Ok(original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MigrationError, Migrations};
+    use serde_json::{json, Value};
+
+    fn rename_gold(document: &mut Value) -> Result<(), String> {
+        let gold = document
+            .as_object_mut()
+            .and_then(|document| document.remove("gold"))
+            .ok_or("missing gold")?;
+        document["money"] = gold;
+        Ok(())
+    }
+
+    fn double_money(document: &mut Value) -> Result<(), String> {
+        let money = document["money"].as_u64().ok_or("missing money")?;
+        document["money"] = Value::from(money * 2);
+        Ok(())
+    }
+
+    #[test]
+    fn migrations_run_in_order() {
+        let migrations = Migrations::new(3)
+            .with_migration(2, double_money)
+            .with_migration(1, rename_gold);
+        let mut document = json!({"version": 1, "gold": 5});
+        assert_eq!(migrations.migrate(&mut document), Ok(1));
+        assert_eq!(document, json!({"version": 3, "money": 10}));
+
+        let mut document = json!({"version": 3, "money": 1});
+        assert_eq!(migrations.migrate(&mut document), Ok(3));
+        assert_eq!(document, json!({"version": 3, "money": 1}));
+
+        assert_eq!(
+            migrations.migrate(&mut json!({"version": 4})),
+            Err(MigrationError::UnsupportedVersion(4))
+        );
+        assert_eq!(
+            migrations.migrate(&mut json!({"version": 0})),
+            Err(MigrationError::MissingMigration(0))
+        );
+        assert_eq!(
+            migrations.migrate(&mut json!({"version": 1})),
+            Err(MigrationError::Failed {
+                from: 1,
+                message: "missing gold".to_string()
+            })
+        );
+        assert_eq!(
+            migrations.migrate(&mut json!({})),
+            Err(MigrationError::MissingVersion)
+        );
+    }
+}
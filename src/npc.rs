@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use crate::area::{AreaIdentifier, CurrentArea, GameAreas};
+use crate::clock::GameClock;
+use crate::state::is_playing;
+
+pub struct NpcPlugin;
+
+/// Non-hostile character. Its [`AreaIdentifier`] is the area it is currently in.
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq)]
+pub struct Npc;
+
+/// Where an NPC should be from a given hour onwards.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScheduleEntry {
+    pub hour: f32,
+    pub area: AreaIdentifier,
+    pub position: Vec2,
+}
+
+impl ScheduleEntry {
+    pub fn new(hour: f32, area: AreaIdentifier, position: Vec2) -> Self {
+        ScheduleEntry {
+            hour,
+            area,
+            position,
+        }
+    }
+}
+
+/// Daily schedule of an NPC, which walks to each entry's position when its hour comes, taking
+/// passages to reach other areas.
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct NpcSchedule {
+    entries: Vec<ScheduleEntry>,
+    speed: f32,
+}
+
+impl NpcSchedule {
+    pub fn new(mut entries: Vec<ScheduleEntry>) -> Self {
+        entries.sort_by(|a, b| a.hour.total_cmp(&b.hour));
+        NpcSchedule {
+            entries,
+            speed: 100.,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Entry in effect at the given hour. Before the first entry of the day, the last one from
+    /// the previous day still applies.
+    pub fn entry_at(&self, hour: f32) -> Option<&ScheduleEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.hour <= hour)
+            .or(self.entries.last())
+    }
+}
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>().add_systems(
+            Update,
+            (
+                place_scheduled_npcs,
+                follow_schedules.run_if(is_playing),
+                npc_visibility,
+            )
+                .chain(),
+        );
+    }
+}
+
+type NpcComponents<'a> = (&'a NpcSchedule, &'a mut Transform, &'a mut AreaIdentifier);
+
+// Newly spawned NPCs start where their schedule currently puts them instead of walking there.
+fn place_scheduled_npcs(
+    clock: Res<GameClock>,
+    mut npc_query: Query<NpcComponents, (With<Npc>, Added<NpcSchedule>)>,
+) {
+    for (schedule, mut transform, mut area) in npc_query.iter_mut() {
+        if let Some(entry) = schedule.entry_at(clock.hour) {
+            *area = entry.area;
+            transform.translation = entry.position.extend(transform.translation.z);
+        }
+    }
+}
+
+fn move_towards(transform: &mut Transform, target: Vec2, distance: f32) -> bool {
+    let position = transform.translation.truncate();
+    let arrived = position.distance(target) <= distance;
+    let next = if arrived {
+        target
+    } else {
+        position + (target - position).normalize() * distance
+    };
+    transform.translation = next.extend(transform.translation.z);
+    arrived
+}
+
+fn follow_schedules(
+    time: Res<Time>,
+    clock: Res<GameClock>,
+    game_areas: Option<Res<GameAreas>>,
+    mut npc_query: Query<NpcComponents, With<Npc>>,
+) {
+    for (schedule, mut transform, mut area) in npc_query.iter_mut() {
+        let Some(entry) = schedule.entry_at(clock.hour) else {
+            continue;
+        };
+        let distance = schedule.speed * time.delta_seconds();
+        if *area == entry.area {
+            if transform.translation.truncate() != entry.position {
+                move_towards(&mut transform, entry.position, distance);
+            }
+            continue;
+        }
+        let passage = game_areas
+            .as_ref()
+            .and_then(|game_areas| game_areas.next_passage(*area, entry.area));
+        let Some(passage) = passage else {
+            // There is no way to walk there, so the NPC shows up directly.
+            *area = entry.area;
+            transform.translation = entry.position.extend(transform.translation.z);
+            continue;
+        };
+        let doorway = passage.transform().translation.truncate();
+        if move_towards(&mut transform, doorway, distance) {
+            let destination = passage.destination();
+            *area = destination.0;
+            let arrival = destination.1.translation.truncate();
+            transform.translation = arrival.extend(transform.translation.z);
+        }
+    }
+}
+
+type NpcMoved = (With<Npc>, Changed<AreaIdentifier>);
+
+fn npc_visibility(
+    current_area: Option<Res<CurrentArea>>,
+    mut npc_query: Query<(&AreaIdentifier, &mut Visibility), NpcMoved>,
+) {
+    let Some(current_area) = current_area else {
+        return;
+    };
+    for (area, mut visibility) in npc_query.iter_mut() {
+        *visibility = if *area == current_area.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Npc, NpcPlugin, NpcSchedule, ScheduleEntry};
+    use crate::area::{Area, AreaIdentifier, CurrentArea, GameAreas, Passage, PassageDestination};
+    use crate::clock::GameClock;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    fn passage(x: f32, destination: usize) -> Passage {
+        Passage::new(
+            Transform::from_xyz(x, 0., 1.),
+            Sprite {
+                custom_size: Some(Vec2::new(30., 80.)),
+                ..default()
+            },
+            PassageDestination(destination.into(), Transform::from_xyz(-x, 0., 1.)),
+        )
+    }
+
+    #[test]
+    fn npcs_walk_their_schedule_across_areas() {
+        let schedule = NpcSchedule::new(vec![
+            ScheduleEntry::new(18., AreaIdentifier(0), Vec2::new(-100., 0.)),
+            ScheduleEntry::new(8., AreaIdentifier(2), Vec2::new(50., 0.)),
+        ]);
+        assert_eq!(schedule.entry_at(3.).unwrap().hour, 18.);
+        assert_eq!(schedule.entry_at(12.).unwrap().hour, 8.);
+
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)));
+        app.insert_resource(GameAreas::new(vec![
+            Area::new(Color::BLACK, vec![passage(600., 1)]),
+            Area::new(Color::BLACK, vec![passage(-600., 0), passage(600., 2)]),
+            Area::new(Color::BLACK, vec![passage(-600., 1)]),
+        ]));
+        app.insert_resource(CurrentArea(0.into()));
+        app.insert_resource(GameClock { day: 0, hour: 20. });
+        app.add_plugins(NpcPlugin);
+        let npc = app
+            .world_mut()
+            .spawn(Npc)
+            .insert(schedule.with_speed(1000.))
+            .insert(SpatialBundle::default())
+            .insert(AreaIdentifier(1))
+            .id();
+        app.update();
+        let position = |app: &App| {
+            let entity = app.world().entity(npc);
+            (
+                *entity.get::<AreaIdentifier>().unwrap(),
+                entity.get::<Transform>().unwrap().translation.truncate(),
+                *entity.get::<Visibility>().unwrap(),
+            )
+        };
+        assert_eq!(
+            position(&app),
+            (AreaIdentifier(0), Vec2::new(-100., 0.), Visibility::Visible)
+        );
+
+        app.insert_resource(GameClock { day: 1, hour: 8. });
+        let mut areas = vec![AreaIdentifier(0)];
+        for _ in 0..20 {
+            app.update();
+            let (area, _, _) = position(&app);
+            if areas.last() != Some(&area) {
+                areas.push(area);
+            }
+        }
+        assert_eq!(areas, vec![0.into(), 1.into(), 2.into()]);
+        assert_eq!(
+            position(&app),
+            (AreaIdentifier(2), Vec2::new(50., 0.), Visibility::Hidden)
+        );
+    }
+}
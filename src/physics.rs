@@ -3,6 +3,9 @@ use bevy_rapier2d::prelude::*;
 
 pub struct PhysicsPlugin;
 
+#[derive(Component, Default)]
+pub struct AreaBounds;
+
 const WORLD_WIDTH: f32 = 1280.;
 const WORLD_HEIGHT: f32 = 720.;
 
@@ -37,7 +40,8 @@ fn add_area_bounds(mut commands: Commands) {
 
     commands
         .spawn(RigidBody::Fixed)
-        .insert(Collider::compound(bounds));
+        .insert(Collider::compound(bounds))
+        .insert(AreaBounds);
 }
 
 impl Plugin for PhysicsPlugin {
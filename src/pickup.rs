@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::Inventory;
+use crate::item::{ItemDatabase, ItemId};
+use crate::player::Player;
+use crate::wallet::Wallet;
+
+pub struct PickupPlugin;
+
+/// Identifies a pickup placed in an area, so that it stays collected.
+#[derive(
+    Clone, Component, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct PickupId(pub usize);
+
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub enum Pickup {
+    Item { item: ItemId, count: u32 },
+    Currency(u32),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Resource, Serialize)]
+pub struct CollectedPickups(pub HashSet<PickupId>);
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct PickedUp {
+    pub collector: Entity,
+    pub pickup: Pickup,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlacedPickup {
+    id: PickupId,
+    transform: Transform,
+    sprite: Sprite,
+    pickup: Pickup,
+}
+
+#[derive(Bundle)]
+pub struct PickupBundle {
+    sprite: SpriteBundle,
+    collider: Collider,
+    sensor: Sensor,
+    active_events: ActiveEvents,
+    pickup: Pickup,
+}
+
+impl PickupBundle {
+    pub fn new(transform: Transform, sprite: Sprite, pickup: Pickup) -> Self {
+        let size = sprite.custom_size.unwrap();
+        PickupBundle {
+            sprite: SpriteBundle {
+                transform,
+                sprite,
+                ..Default::default()
+            },
+            collider: Collider::cuboid(size.x / 2., size.y / 2.),
+            sensor: Sensor,
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            pickup,
+        }
+    }
+}
+
+impl PlacedPickup {
+    pub fn new(id: PickupId, transform: Transform, sprite: Sprite, pickup: Pickup) -> Self {
+        PlacedPickup {
+            id,
+            transform,
+            sprite,
+            pickup,
+        }
+    }
+
+    pub fn id(&self) -> PickupId {
+        self.id
+    }
+
+    pub(crate) fn bundle(&self) -> (PickupBundle, PickupId) {
+        (
+            PickupBundle::new(self.transform, self.sprite.clone(), self.pickup),
+            self.id,
+        )
+    }
+}
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollectedPickups>()
+            .add_event::<PickedUp>()
+            .add_systems(Update, pickup_check);
+    }
+}
+
+fn pickup_check(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut player_query: Query<(&mut Inventory, Option<&mut Wallet>), With<Player>>,
+    pickup_query: Query<(&Pickup, Option<&PickupId>)>,
+    database: Res<ItemDatabase>,
+    mut collected: ResMut<CollectedPickups>,
+    mut ev_picked_up: EventWriter<PickedUp>,
+) {
+    let mut despawned = HashSet::new();
+    for event in collision_events.read() {
+        let CollisionEvent::Started(l, r, _) = event else {
+            continue;
+        };
+        let (player, other) = if player_query.contains(*l) {
+            (*l, *r)
+        } else if player_query.contains(*r) {
+            (*r, *l)
+        } else {
+            continue;
+        };
+        let Ok((&pickup, id)) = pickup_query.get(other) else {
+            continue;
+        };
+        if despawned.contains(&other) {
+            continue;
+        }
+        let (mut inventory, wallet) = player_query.get_mut(player).unwrap();
+        match pickup {
+            Pickup::Item { item, count } => {
+                if inventory.add(&database, item, count).is_err() {
+                    continue;
+                }
+            }
+            Pickup::Currency(amount) => {
+                let Some(mut wallet) = wallet else {
+                    continue;
+                };
+                wallet.deposit(amount);
+            }
+        }
+        if let Some(&id) = id {
+            collected.0.insert(id);
+        }
+        despawned.insert(other);
+        commands.entity(other).despawn_recursive();
+        ev_picked_up.send(PickedUp {
+            collector: player,
+            pickup,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollectedPickups, PickedUp, Pickup, PickupId, PickupPlugin, PlacedPickup};
+    use crate::inventory::Inventory;
+    use crate::item::test_utils::material;
+    use crate::item::{ItemCategory, ItemDatabase, ItemDefinition, ItemId};
+    use crate::player::{self, Player};
+    use bevy::prelude::*;
+    use bevy_rapier2d::prelude::*;
+
+    fn get_test_app() -> App {
+        let mut ret = App::default();
+        ret.add_plugins(MinimalPlugins);
+        ret.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        ret.add_plugins(bevy::asset::AssetPlugin::default());
+        ret.insert_resource(ItemDatabase::new(vec![ItemDefinition {
+            category: ItemCategory::Misc,
+            ..material(ItemId(3), "Coin pouch")
+        }]));
+        ret.add_plugins(PickupPlugin);
+        ret
+    }
+
+    #[test]
+    fn player_collects_pickup() {
+        let mut app = get_test_app();
+        app.add_plugins(player::test_utils::PlayerPlugin);
+        let placed = PlacedPickup::new(
+            PickupId(7),
+            Transform::from_xyz(10., 10., 1.),
+            Sprite {
+                custom_size: Some(Vec2::new(4., 4.)),
+                ..default()
+            },
+            Pickup::Item {
+                item: ItemId(3),
+                count: 2,
+            },
+        );
+        let pickup = app.world_mut().spawn(placed.bundle()).id();
+        app.update();
+        app.update();
+
+        assert!(app.world().get_entity(pickup).is_none());
+        let inventory = app
+            .world_mut()
+            .query_filtered::<&Inventory, With<Player>>()
+            .single(app.world())
+            .clone();
+        assert_eq!(inventory.count(ItemId(3)), 2);
+        assert!(app
+            .world()
+            .resource::<CollectedPickups>()
+            .0
+            .contains(&PickupId(7)));
+        let events = app.world().resource::<Events<PickedUp>>();
+        assert_eq!(events.get_reader().read(events).count(), 1);
+    }
+}
@@ -1,17 +1,53 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::area::{AreaIdentifier, PassageDestination};
+use crate::combat::{Faction, Health};
+use crate::consumable::{ItemCooldowns, ReturnPoint};
+use crate::crafting::KnownRecipes;
+use crate::equipment::Equipment;
+use crate::inventory::Inventory;
+use crate::progression::{AttributeGrowth, ProgressionBundle};
+use crate::projectile::{Projectile, RangedAttack, RangedAttackEvent};
+use crate::quest::QuestLog;
+use crate::state::is_playing;
+use crate::stats::{Attributes, DerivedStats, StatsBundle, TimedModifiers};
+use crate::wallet::Wallet;
+
 #[derive(Component, Default)]
 pub struct Player {}
 
+/// Direction the player last moved in.
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub struct Facing(pub Vec2);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Facing(Vec2::X)
+    }
+}
+
+/// Keeps movement input from overriding the player's velocity, e.g. while being knocked back.
+#[derive(Clone, Component, Debug)]
+pub struct InputLockout(pub Timer);
+
+impl InputLockout {
+    pub fn new(duration: f32) -> Self {
+        InputLockout(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
 pub struct PlayerPlugin;
 
 const PLAYER_SIDE: f32 = 60.;
+const INVENTORY_CAPACITY: usize = 20;
 
 fn setup(mut commands: Commands) {
+    let stats = StatsBundle::new(Attributes::default());
+    let transform = Transform::from_xyz(0., 0., 0.);
     commands
         .spawn(SpriteBundle {
-            transform: Transform::from_xyz(0., 0., 0.),
+            transform,
             sprite: Sprite {
                 color: Color::srgb(0., 0., 1.),
                 custom_size: Some(Vec2::new(PLAYER_SIDE, PLAYER_SIDE)),
@@ -23,24 +59,73 @@ fn setup(mut commands: Commands) {
         .insert(ColliderMassProperties::Density(0.))
         .insert(AdditionalMassProperties::Mass(10.))
         .insert(Player::default())
+        .insert(Facing::default())
+        .insert(Faction::Player)
+        .insert(Health::new(stats.derived().max_health))
+        .insert(stats)
+        .insert(ProgressionBundle::default())
+        .insert(Inventory::new(INVENTORY_CAPACITY))
+        .insert(Equipment::default())
+        .insert(ItemCooldowns::default())
+        .insert(ReturnPoint(PassageDestination(
+            AreaIdentifier(0),
+            transform,
+        )))
+        .insert(Wallet::default())
+        .insert(KnownRecipes::default())
+        .insert(QuestLog::default())
+        .insert(TimedModifiers::default())
+        .insert(AttributeGrowth(Attributes {
+            strength: 1.,
+            dexterity: 1.,
+            intelligence: 1.,
+            vitality: 1.,
+        }))
+        .insert(RangedAttack::new(
+            Projectile::new(640., 1.5, 0, 10., Faction::Player),
+            Sprite {
+                color: Color::srgb(1., 1., 0.),
+                custom_size: Some(Vec2::new(12., 12.)),
+                ..default()
+            },
+            0.4,
+        ))
         .insert(RigidBody::Dynamic)
         .insert(Velocity::default());
 }
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, player_movement_system);
+        app.add_event::<RangedAttackEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, input_lockout_system.before(player_movement_system))
+            .add_systems(Update, player_movement_system.run_if(is_playing))
+            .add_systems(Update, player_ranged_attack_system.run_if(is_playing));
+    }
+}
+
+fn input_lockout_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lockout_query: Query<(Entity, &mut InputLockout)>,
+) {
+    for (entity, mut lockout) in lockout_query.iter_mut() {
+        if lockout.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<InputLockout>();
+        }
     }
 }
 
 fn player_movement_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&Player, &mut Velocity)>,
+    mut player_query: Query<
+        (&Player, &mut Velocity, &mut Facing, Option<&DerivedStats>),
+        Without<InputLockout>,
+    >,
 ) {
     const SPEED: f32 = 384.;
 
-    if let Ok((_, mut velocity)) = player_query.get_single_mut() {
+    if let Ok((_, mut velocity, mut facing, stats)) = player_query.get_single_mut() {
         let mut direction = Vec2::ZERO;
 
         if keyboard_input.pressed(KeyCode::KeyD) {
@@ -57,11 +142,30 @@ fn player_movement_system(
         }
 
         direction = direction.normalize_or_zero();
-        direction *= SPEED;
+        if direction != Vec2::ZERO {
+            facing.0 = direction;
+        }
+        direction *= stats.map_or(SPEED, |stats| stats.move_speed);
         velocity.linvel = direction;
     }
 }
 
+fn player_ranged_attack_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_query: Query<(Entity, &Facing), With<Player>>,
+    mut ev_ranged_attack: EventWriter<RangedAttackEvent>,
+) {
+    if !keyboard_input.pressed(KeyCode::Space) {
+        return;
+    }
+    if let Ok((shooter, facing)) = player_query.get_single() {
+        ev_ranged_attack.send(RangedAttackEvent {
+            shooter,
+            direction: facing.0,
+        });
+    }
+}
+
 #[cfg(test)]
 pub mod test_utils {
     use bevy::prelude::*;
@@ -74,3 +178,35 @@ pub mod test_utils {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Player, PlayerPlugin};
+    use crate::projectile::RangedAttackEvent;
+    use bevy::prelude::*;
+
+    #[test]
+    fn player_plugin_runs_on_its_own() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_plugins(PlayerPlugin);
+        app.update();
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(app.world());
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+        let events = app.world().resource::<Events<RangedAttackEvent>>();
+        let shooters: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|event| event.shooter)
+            .collect();
+        assert_eq!(shooters, vec![player]);
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::combat::{apply_damage, Defeated};
+use crate::player::Player;
+use crate::stats::Attributes;
+
+pub struct ProgressionPlugin;
+
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Experience(pub u32);
+
+#[derive(Clone, Component, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Level(pub u32);
+
+impl Default for Level {
+    fn default() -> Self {
+        Level(1)
+    }
+}
+
+/// Attributes gained on every level up.
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub struct AttributeGrowth(pub Attributes);
+
+/// Experience granted to the player when this entity is defeated.
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub struct ExperienceReward(pub u32);
+
+/// Total experience needed to reach each level.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub enum LevelCurve {
+    /// `base * (level - 1)^exponent`, without a level cap.
+    Polynomial { base: f32, exponent: f32 },
+    /// Thresholds for levels 2, 3, ...; the last entry is the level cap.
+    Table(Vec<u32>),
+}
+
+impl Default for LevelCurve {
+    fn default() -> Self {
+        LevelCurve::Polynomial {
+            base: 100.,
+            exponent: 1.5,
+        }
+    }
+}
+
+impl LevelCurve {
+    /// Rejects curves whose thresholds never grow, i.e. a non-positive `base` or `exponent`.
+    pub fn polynomial(base: f32, exponent: f32) -> Option<Self> {
+        (base > 0. && exponent > 0.).then_some(LevelCurve::Polynomial { base, exponent })
+    }
+
+    /// Rejects tables whose thresholds don't strictly increase.
+    pub fn table(thresholds: Vec<u32>) -> Option<Self> {
+        let increasing = thresholds.first().is_none_or(|&first| first > 0)
+            && thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        increasing.then_some(LevelCurve::Table(thresholds))
+    }
+
+    pub fn experience_for_level(&self, level: u32) -> Option<u32> {
+        if level <= 1 {
+            return Some(0);
+        }
+        match self {
+            LevelCurve::Polynomial { base, exponent } => {
+                Some((base * ((level - 1) as f32).powf(*exponent)).round() as u32)
+            }
+            LevelCurve::Table(thresholds) => thresholds.get(level as usize - 2).copied(),
+        }
+    }
+
+    /// Levels stop once a threshold no longer grows, which also caps polynomial curves when
+    /// they run out of `f32` precision or saturate at `u32::MAX`.
+    pub fn level_for_experience(&self, experience: u32) -> u32 {
+        let mut level = 1;
+        let mut previous = 0;
+        while let Some(required) = self.experience_for_level(level + 1) {
+            if required <= previous || experience < required {
+                break;
+            }
+            previous = required;
+            level += 1;
+        }
+        level
+    }
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct ExperienceGained {
+    pub entity: Entity,
+    pub amount: u32,
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct LevelUp {
+    pub entity: Entity,
+    pub level: Level,
+}
+
+#[derive(Bundle, Default)]
+pub struct ProgressionBundle {
+    experience: Experience,
+    level: Level,
+}
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelCurve>()
+            .add_event::<ExperienceGained>()
+            .add_event::<LevelUp>()
+            .add_systems(
+                Update,
+                (reward_experience, gain_experience)
+                    .chain()
+                    .after(apply_damage),
+            );
+    }
+}
+
+fn reward_experience(
+    mut ev_defeated: EventReader<Defeated>,
+    reward_query: Query<&ExperienceReward>,
+    player_query: Query<Entity, With<Player>>,
+    mut ev_experience: EventWriter<ExperienceGained>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for defeated in ev_defeated.read() {
+        if let Ok(reward) = reward_query.get(defeated.entity) {
+            ev_experience.send(ExperienceGained {
+                entity: player,
+                amount: reward.0,
+            });
+        }
+    }
+}
+
+fn gain_experience(
+    curve: Res<LevelCurve>,
+    mut ev_experience: EventReader<ExperienceGained>,
+    mut progression_query: Query<(
+        &mut Experience,
+        &mut Level,
+        Option<&AttributeGrowth>,
+        Option<&mut Attributes>,
+    )>,
+    mut ev_level_up: EventWriter<LevelUp>,
+) {
+    for gained in ev_experience.read() {
+        let Ok((mut experience, mut level, growth, mut attributes)) =
+            progression_query.get_mut(gained.entity)
+        else {
+            continue;
+        };
+        experience.0 = experience.0.saturating_add(gained.amount);
+        let new_level = curve.level_for_experience(experience.0);
+        while level.0 < new_level {
+            level.0 += 1;
+            if let (Some(growth), Some(attributes)) = (growth, attributes.as_mut()) {
+                attributes.strength += growth.0.strength;
+                attributes.dexterity += growth.0.dexterity;
+                attributes.intelligence += growth.0.intelligence;
+                attributes.vitality += growth.0.vitality;
+            }
+            ev_level_up.send(LevelUp {
+                entity: gained.entity,
+                level: *level,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AttributeGrowth, Experience, ExperienceGained, Level, LevelCurve, LevelUp,
+        ProgressionBundle,
+    };
+    use crate::stats::Attributes;
+    use bevy::prelude::*;
+
+    #[test]
+    fn level_curves() {
+        let polynomial = LevelCurve::default();
+        assert_eq!(polynomial.experience_for_level(1), Some(0));
+        assert_eq!(polynomial.experience_for_level(2), Some(100));
+        assert_eq!(polynomial.experience_for_level(5), Some(800));
+        assert_eq!(polynomial.level_for_experience(799), 4);
+
+        let table = LevelCurve::Table(vec![10, 30]);
+        assert_eq!(table.level_for_experience(9), 1);
+        assert_eq!(table.level_for_experience(30), 3);
+        assert_eq!(table.level_for_experience(1000), 3);
+
+        assert_eq!(LevelCurve::polynomial(0., 1.5), None);
+        assert_eq!(LevelCurve::polynomial(100., 0.), None);
+        assert_eq!(LevelCurve::table(vec![10, 10]), None);
+        let flat = LevelCurve::Polynomial {
+            base: 0.,
+            exponent: 1.5,
+        };
+        assert_eq!(flat.level_for_experience(u32::MAX), 1);
+        assert!(polynomial.level_for_experience(u32::MAX) > 100_000);
+    }
+
+    #[test]
+    fn experience_levels_up_with_growth() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(LevelCurve::Table(vec![10, 30, 60]));
+        app.add_event::<ExperienceGained>();
+        app.add_event::<LevelUp>();
+        app.add_systems(Update, super::gain_experience);
+        let entity = app
+            .world_mut()
+            .spawn(ProgressionBundle::default())
+            .insert(Attributes::default())
+            .insert(AttributeGrowth(Attributes {
+                strength: 2.,
+                dexterity: 1.,
+                intelligence: 0.,
+                vitality: 1.,
+            }))
+            .id();
+        app.world_mut()
+            .send_event(ExperienceGained { entity, amount: 35 });
+        app.update();
+
+        assert_eq!(app.world().get::<Experience>(entity), Some(&Experience(35)));
+        assert_eq!(app.world().get::<Level>(entity), Some(&Level(3)));
+        assert_eq!(app.world().get::<Attributes>(entity).unwrap().strength, 14.);
+        let events = app.world().resource::<Events<LevelUp>>();
+        let levels: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|level_up| level_up.level)
+            .collect();
+        assert_eq!(levels, vec![Level(2), Level(3)]);
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::area::{AreaEntered, AreaIdentifier, CurrentArea};
+use crate::combat::{DamageEvent, Faction, Health};
+use crate::physics::AreaBounds;
+use crate::stats::DerivedStats;
+
+pub struct ProjectilePlugin;
+
+#[derive(Clone, Component, Debug)]
+pub struct Projectile {
+    pub speed: f32,
+    pub lifetime: Timer,
+    /// Number of targets the projectile passes through before it is spent.
+    pub pierce: u32,
+    pub damage: f32,
+    pub owner: Faction,
+}
+
+impl Projectile {
+    pub fn new(speed: f32, lifetime: f32, pierce: u32, damage: f32, owner: Faction) -> Self {
+        Projectile {
+            speed,
+            lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+            pierce,
+            damage,
+            owner,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct ProjectileBundle {
+    sprite: SpriteBundle,
+    projectile: Projectile,
+    area: AreaIdentifier,
+    rigid_body: RigidBody,
+    collider: Collider,
+    sensor: Sensor,
+    gravity_scale: GravityScale,
+    velocity: Velocity,
+    active_events: ActiveEvents,
+}
+
+impl ProjectileBundle {
+    pub fn new(
+        projectile: Projectile,
+        sprite: Sprite,
+        origin: Vec2,
+        direction: Vec2,
+        area: AreaIdentifier,
+    ) -> Self {
+        let radius = sprite.custom_size.unwrap().x / 2.;
+        let linvel = direction.normalize_or_zero() * projectile.speed;
+        ProjectileBundle {
+            sprite: SpriteBundle {
+                transform: Transform::from_translation(origin.extend(1.)),
+                sprite,
+                ..Default::default()
+            },
+            projectile,
+            area,
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::ball(radius),
+            sensor: Sensor,
+            gravity_scale: GravityScale(0.),
+            velocity: Velocity::linear(linvel),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+        }
+    }
+}
+
+/// Lets an entity fire projectiles through [`RangedAttackEvent`]s.
+#[derive(Clone, Component, Debug)]
+pub struct RangedAttack {
+    pub projectile: Projectile,
+    pub sprite: Sprite,
+    pub cooldown: Timer,
+}
+
+impl RangedAttack {
+    pub fn new(projectile: Projectile, sprite: Sprite, cooldown: f32) -> Self {
+        let mut cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        RangedAttack {
+            projectile,
+            sprite,
+            cooldown,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct RangedAttackEvent {
+    pub shooter: Entity,
+    pub direction: Vec2,
+}
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RangedAttackEvent>()
+            .add_systems(Update, ranged_attack)
+            .add_systems(Update, projectile_lifetime)
+            .add_systems(Update, projectile_collisions)
+            .add_systems(Update, projectile_area_cleanup);
+    }
+}
+
+fn ranged_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    current_area: Option<Res<CurrentArea>>,
+    mut ev_ranged_attack: EventReader<RangedAttackEvent>,
+    mut shooters: Query<(&mut RangedAttack, &Transform, Option<&DerivedStats>)>,
+) {
+    for (mut attack, _, _) in shooters.iter_mut() {
+        attack.cooldown.tick(time.delta());
+    }
+    let area = current_area.map_or(0.into(), |current_area| current_area.0);
+    for event in ev_ranged_attack.read() {
+        let Ok((mut attack, transform, stats)) = shooters.get_mut(event.shooter) else {
+            continue;
+        };
+        if !attack.cooldown.finished() {
+            continue;
+        }
+        attack.cooldown.reset();
+        let mut projectile = attack.projectile.clone();
+        projectile.damage += stats.map_or(0., |stats| stats.attack);
+        commands.spawn(ProjectileBundle::new(
+            projectile,
+            attack.sprite.clone(),
+            transform.translation.truncate(),
+            event.direction,
+            area,
+        ));
+    }
+}
+
+fn projectile_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectiles: Query<(Entity, &mut Projectile)>,
+) {
+    for (entity, mut projectile) in projectiles.iter_mut() {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn projectile_collisions(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut projectiles: Query<&mut Projectile>,
+    targets: Query<&Faction, With<Health>>,
+    bounds: Query<(), With<AreaBounds>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    let mut spent = HashSet::new();
+    for event in collision_events.read() {
+        let CollisionEvent::Started(l, r, _) = event else {
+            continue;
+        };
+        let (projectile_entity, other) = if projectiles.contains(*l) {
+            (*l, *r)
+        } else if projectiles.contains(*r) {
+            (*r, *l)
+        } else {
+            continue;
+        };
+        if spent.contains(&projectile_entity) {
+            continue;
+        }
+        let mut projectile = projectiles.get_mut(projectile_entity).unwrap();
+        if bounds.contains(other) {
+            spent.insert(projectile_entity);
+        } else if let Ok(&faction) = targets.get(other) {
+            if faction == projectile.owner {
+                continue;
+            }
+            ev_damage.send(DamageEvent {
+                target: other,
+                amount: projectile.damage,
+            });
+            if projectile.pierce == 0 {
+                spent.insert(projectile_entity);
+            } else {
+                projectile.pierce -= 1;
+            }
+        }
+    }
+    for projectile in spent {
+        commands.entity(projectile).despawn();
+    }
+}
+
+fn projectile_area_cleanup(
+    mut commands: Commands,
+    mut ev_area_entered: EventReader<AreaEntered>,
+    projectiles: Query<Entity, With<Projectile>>,
+) {
+    if ev_area_entered.read().next().is_some() {
+        for projectile in projectiles.iter() {
+            commands.entity(projectile).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Projectile, ProjectileBundle};
+    use crate::combat::{DamageEvent, Faction, Health};
+    use crate::physics::AreaBounds;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_rapier2d::prelude::*;
+    use std::time::Duration;
+
+    fn get_test_app() -> App {
+        let mut ret = App::default();
+        ret.add_plugins(MinimalPlugins);
+        ret.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        ret.add_plugins(bevy::asset::AssetPlugin::default());
+        ret.add_event::<DamageEvent>();
+        ret
+    }
+
+    fn spawn_projectile(world: &mut World, pierce: u32) -> Entity {
+        world
+            .spawn(ProjectileBundle::new(
+                Projectile::new(1., 10., pierce, 5., Faction::Player),
+                Sprite {
+                    custom_size: Some(Vec2::new(4., 4.)),
+                    ..default()
+                },
+                Vec2::new(10., 10.),
+                Vec2::X,
+                0.into(),
+            ))
+            .id()
+    }
+
+    fn spawn_target(world: &mut World, faction: Faction, position: Vec2) -> Entity {
+        world
+            .spawn(TransformBundle::from_transform(
+                Transform::from_translation(position.extend(0.)),
+            ))
+            .insert(Collider::cuboid(4., 4.))
+            .insert(RigidBody::Fixed)
+            .insert(faction)
+            .insert(Health::new(20.))
+            .id()
+    }
+
+    fn damage_events(app: &App) -> Vec<DamageEvent> {
+        let events = app.world().resource::<Events<DamageEvent>>();
+        events.get_reader().read(events).copied().collect()
+    }
+
+    #[test]
+    fn projectile_damages_opposing_faction_and_is_spent() {
+        let mut app = get_test_app();
+        let projectile = spawn_projectile(app.world_mut(), 0);
+        let enemy = spawn_target(app.world_mut(), Faction::Enemy, Vec2::new(11., 10.));
+        app.add_systems(Last, super::projectile_collisions);
+        app.update();
+        assert_eq!(
+            damage_events(&app),
+            vec![DamageEvent {
+                target: enemy,
+                amount: 5.
+            }]
+        );
+        assert!(app.world().get_entity(projectile).is_none());
+    }
+
+    #[test]
+    fn piercing_projectile_survives_hit() {
+        let mut app = get_test_app();
+        let projectile = spawn_projectile(app.world_mut(), 1);
+        spawn_target(app.world_mut(), Faction::Enemy, Vec2::new(11., 10.));
+        app.add_systems(Last, super::projectile_collisions);
+        app.update();
+        assert_eq!(damage_events(&app).len(), 1);
+        assert_eq!(app.world().get::<Projectile>(projectile).unwrap().pierce, 0);
+    }
+
+    #[test]
+    fn projectile_ignores_own_faction() {
+        let mut app = get_test_app();
+        let projectile = spawn_projectile(app.world_mut(), 0);
+        spawn_target(app.world_mut(), Faction::Player, Vec2::new(11., 10.));
+        app.add_systems(Last, super::projectile_collisions);
+        app.update();
+        assert!(damage_events(&app).is_empty());
+        assert!(app.world().get_entity(projectile).is_some());
+    }
+
+    #[test]
+    fn projectile_is_despawned_by_area_bounds() {
+        let mut app = get_test_app();
+        let projectile = spawn_projectile(app.world_mut(), 3);
+        app.world_mut()
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                11., 10., 0.,
+            )))
+            .insert(Collider::cuboid(4., 4.))
+            .insert(RigidBody::Fixed)
+            .insert(AreaBounds);
+        app.add_systems(Last, super::projectile_collisions);
+        app.update();
+        assert!(app.world().get_entity(projectile).is_none());
+    }
+
+    #[test]
+    fn projectile_expires() {
+        let mut app = get_test_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+        let projectile = spawn_projectile(app.world_mut(), 0);
+        app.add_systems(Update, super::projectile_lifetime);
+        for _ in 0..20 {
+            app.update();
+        }
+        assert!(app.world().get_entity(projectile).is_some());
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(app.world().get_entity(projectile).is_none());
+    }
+}
@@ -0,0 +1,585 @@
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::area::{AreaEntered, AreaIdentifier};
+use crate::combat::{apply_damage, Defeated};
+use crate::dialogue::DialogueStarted;
+use crate::enemy::Enemy;
+use crate::flags::WorldFlags;
+use crate::inventory::{Inventory, InventoryChanged};
+use crate::item::{ItemDatabase, ItemId};
+use crate::player::Player;
+use crate::progression::ExperienceGained;
+use crate::state::is_playing;
+use crate::wallet::Wallet;
+
+pub struct QuestPlugin;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Objective {
+    /// Defeat a number of [`Enemy`] entities.
+    Kill {
+        count: u32,
+    },
+    /// Hold a number of items at once.
+    Collect {
+        item: ItemId,
+        count: u32,
+    },
+    Reach(AreaIdentifier),
+    /// Start the dialogue with the given id.
+    TalkTo(String),
+}
+
+impl Objective {
+    pub fn required(&self) -> u32 {
+        match self {
+            Objective::Kill { count } | Objective::Collect { count, .. } => *count,
+            Objective::Reach(_) | Objective::TalkTo(_) => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum QuestReward {
+    Experience(u32),
+    Currency(u32),
+    Item { item: ItemId, count: u32 },
+    Flag(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestStage {
+    pub description: String,
+    pub objectives: Vec<Objective>,
+}
+
+/// Quest whose stages are completed in order, granting its rewards after the last one.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(deserialize_with = "non_empty_stages")]
+    pub stages: Vec<QuestStage>,
+    #[serde(default)]
+    pub rewards: Vec<QuestReward>,
+}
+
+fn non_empty_stages<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<QuestStage>, D::Error> {
+    let stages = Vec::<QuestStage>::deserialize(deserializer)?;
+    if stages.is_empty() {
+        return Err(D::Error::invalid_length(0, &"at least one stage"));
+    }
+    Ok(stages)
+}
+
+#[derive(Clone, Debug, Default, Resource)]
+pub struct QuestDatabase {
+    quests: Vec<QuestDefinition>,
+}
+
+impl QuestDatabase {
+    pub fn new(quests: Vec<QuestDefinition>) -> Self {
+        QuestDatabase { quests }
+    }
+
+    /// Parses a list of quests written in RON, with the same extensions as
+    /// [`DialogueTree::from_ron`](crate::dialogue::DialogueTree::from_ron).
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .map(QuestDatabase::new)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&QuestDefinition> {
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestProgress {
+    pub quest: String,
+    pub stage: usize,
+    /// Progress towards each objective of the current stage.
+    pub objectives: Vec<u32>,
+    pub completed: bool,
+}
+
+/// Quests an entity has started, in the order they were started.
+#[derive(Clone, Component, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct QuestLog(pub Vec<QuestProgress>);
+
+impl QuestLog {
+    pub fn get(&self, quest: &str) -> Option<&QuestProgress> {
+        self.0.iter().find(|progress| progress.quest == quest)
+    }
+
+    pub fn is_completed(&self, quest: &str) -> bool {
+        self.get(quest).is_some_and(|progress| progress.completed)
+    }
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct StartQuest {
+    pub quest: String,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct QuestStarted {
+    pub quest: String,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct QuestStageCompleted {
+    pub quest: String,
+    pub stage: usize,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct QuestCompleted {
+    pub quest: String,
+}
+
+#[derive(Component)]
+struct QuestLogScreen;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuestDatabase>()
+            .add_event::<StartQuest>()
+            .add_event::<QuestStarted>()
+            .add_event::<QuestStageCompleted>()
+            .add_event::<QuestCompleted>()
+            .add_systems(Update, start_quests)
+            .add_systems(Update, track_quests.after(start_quests).after(apply_damage))
+            .add_systems(Update, toggle_quest_log.run_if(is_playing))
+            .add_systems(Update, quest_log_text.after(toggle_quest_log));
+    }
+}
+
+fn start_quests(
+    mut ev_start_quest: EventReader<StartQuest>,
+    database: Res<QuestDatabase>,
+    mut log_query: Query<&mut QuestLog, With<Player>>,
+    mut ev_started: EventWriter<QuestStarted>,
+) {
+    let Ok(mut log) = log_query.get_single_mut() else {
+        return;
+    };
+    for start in ev_start_quest.read() {
+        let Some(definition) = database.get(&start.quest) else {
+            warn!("Unknown quest {}", start.quest);
+            continue;
+        };
+        if log.get(&start.quest).is_some() {
+            continue;
+        }
+        let Some(first) = definition.stages.first() else {
+            warn!("Quest {} has no stages", start.quest);
+            continue;
+        };
+        log.0.push(QuestProgress {
+            quest: start.quest.clone(),
+            stage: 0,
+            objectives: vec![0; first.objectives.len()],
+            completed: false,
+        });
+        ev_started.send(QuestStarted {
+            quest: start.quest.clone(),
+        });
+    }
+}
+
+enum QuestTrigger<'a> {
+    Kill,
+    Reach(AreaIdentifier),
+    TalkTo(&'a str),
+}
+
+impl QuestTrigger<'_> {
+    fn advances(&self, objective: &Objective) -> bool {
+        match (self, objective) {
+            (QuestTrigger::Kill, Objective::Kill { .. }) => true,
+            (QuestTrigger::Reach(entered), Objective::Reach(area)) => entered == area,
+            (QuestTrigger::TalkTo(started), Objective::TalkTo(tree)) => started == tree,
+            _ => false,
+        }
+    }
+}
+
+fn grant_rewards(
+    entity: Entity,
+    rewards: &[QuestReward],
+    database: &ItemDatabase,
+    inventory: Option<&mut Inventory>,
+    wallet: Option<&mut Wallet>,
+    flags: Option<&mut WorldFlags>,
+    ev_experience: &mut EventWriter<ExperienceGained>,
+) {
+    let (mut inventory, mut wallet, mut flags) = (inventory, wallet, flags);
+    for reward in rewards {
+        match reward {
+            QuestReward::Experience(amount) => {
+                ev_experience.send(ExperienceGained {
+                    entity,
+                    amount: *amount,
+                });
+            }
+            QuestReward::Currency(amount) => {
+                if let Some(wallet) = wallet.as_deref_mut() {
+                    wallet.deposit(*amount);
+                }
+            }
+            QuestReward::Item { item, count } => {
+                if let Some(inventory) = inventory.as_deref_mut() {
+                    if let Err(error) = inventory.add(database, *item, *count) {
+                        warn!("Couldn't grant quest reward: {error}");
+                    }
+                }
+            }
+            QuestReward::Flag(key) => {
+                if let Some(flags) = flags.as_deref_mut() {
+                    flags.set_bool(key.clone(), true);
+                }
+            }
+        }
+    }
+}
+
+type QuesterComponents<'a> = (
+    Entity,
+    &'a mut QuestLog,
+    Option<&'a mut Inventory>,
+    Option<&'a mut Wallet>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn track_quests(
+    mut ev_defeated: EventReader<Defeated>,
+    mut ev_area_entered: EventReader<AreaEntered>,
+    mut ev_dialogue_started: EventReader<DialogueStarted>,
+    mut ev_inventory_changed: EventReader<InventoryChanged>,
+    mut ev_started: EventReader<QuestStarted>,
+    enemy_query: Query<(), With<Enemy>>,
+    database: Res<QuestDatabase>,
+    items: Res<ItemDatabase>,
+    mut flags: Option<ResMut<WorldFlags>>,
+    mut quester_query: Query<QuesterComponents, With<Player>>,
+    mut ev_stage_completed: EventWriter<QuestStageCompleted>,
+    mut ev_completed: EventWriter<QuestCompleted>,
+    mut ev_experience: EventWriter<ExperienceGained>,
+) {
+    let mut triggers: Vec<QuestTrigger> = ev_defeated
+        .read()
+        .filter(|defeated| enemy_query.contains(defeated.entity))
+        .map(|_| QuestTrigger::Kill)
+        .collect();
+    triggers.extend(
+        ev_area_entered
+            .read()
+            .map(|entered| QuestTrigger::Reach(entered.0)),
+    );
+    triggers.extend(
+        ev_dialogue_started
+            .read()
+            .map(|started| QuestTrigger::TalkTo(&started.tree)),
+    );
+    let mut refresh = ev_inventory_changed.read().count() > 0;
+    refresh |= ev_started.read().count() > 0;
+    if triggers.is_empty() && !refresh {
+        return;
+    }
+    let Ok((entity, mut log, mut inventory, mut wallet)) = quester_query.get_single_mut() else {
+        return;
+    };
+    for progress in log.0.iter_mut().filter(|progress| !progress.completed) {
+        let Some(definition) = database.get(&progress.quest) else {
+            continue;
+        };
+        loop {
+            let Some(stage) = definition.stages.get(progress.stage) else {
+                warn!("Quest {} has no stage {}", progress.quest, progress.stage);
+                break;
+            };
+            for (objective, count) in stage.objectives.iter().zip(&mut progress.objectives) {
+                if let Objective::Collect { item, .. } = objective {
+                    *count = inventory
+                        .as_ref()
+                        .map_or(0, |inventory| inventory.count(*item));
+                }
+                let advanced = triggers
+                    .iter()
+                    .filter(|trigger| trigger.advances(objective))
+                    .count() as u32;
+                *count = (*count + advanced).min(objective.required());
+            }
+            let done = stage
+                .objectives
+                .iter()
+                .zip(&progress.objectives)
+                .all(|(objective, count)| *count >= objective.required());
+            if !done {
+                break;
+            }
+            ev_stage_completed.send(QuestStageCompleted {
+                quest: progress.quest.clone(),
+                stage: progress.stage,
+            });
+            progress.stage += 1;
+            let Some(next) = definition.stages.get(progress.stage) else {
+                progress.completed = true;
+                grant_rewards(
+                    entity,
+                    &definition.rewards,
+                    &items,
+                    inventory.as_deref_mut(),
+                    wallet.as_deref_mut(),
+                    flags.as_deref_mut(),
+                    &mut ev_experience,
+                );
+                ev_completed.send(QuestCompleted {
+                    quest: progress.quest.clone(),
+                });
+                break;
+            };
+            // Triggers only count towards the stage that was active when they happened.
+            progress.objectives = vec![0; next.objectives.len()];
+            triggers.clear();
+        }
+    }
+}
+
+fn toggle_quest_log(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    screen_query: Query<Entity, With<QuestLogScreen>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyJ) {
+        return;
+    }
+    if !screen_query.is_empty() {
+        for screen in screen_query.iter() {
+            commands.entity(screen).despawn_recursive();
+        }
+        return;
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(40.),
+                top: Val::Px(40.),
+                padding: UiRect::all(Val::Px(16.)),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.8).into(),
+            ..default()
+        })
+        .insert(QuestLogScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn objective_text(objective: &Objective, count: u32, items: &ItemDatabase) -> String {
+    let required = objective.required();
+    match objective {
+        Objective::Kill { .. } => format!("Defeat enemies {count}/{required}"),
+        Objective::Collect { item, .. } => {
+            let name = items
+                .get(*item)
+                .map_or("?", |definition| definition.name.as_str());
+            format!("Collect {name} {count}/{required}")
+        }
+        Objective::Reach(area) => format!("Reach area {} {count}/{required}", area.0),
+        Objective::TalkTo(tree) => format!("Talk to {tree} {count}/{required}"),
+    }
+}
+
+fn quest_log_text(
+    database: Res<QuestDatabase>,
+    items: Res<ItemDatabase>,
+    log_query: Query<Ref<QuestLog>, With<Player>>,
+    screen_query: Query<(&Children, Ref<QuestLogScreen>)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let Ok(log) = log_query.get_single() else {
+        return;
+    };
+    for (children, screen) in screen_query.iter() {
+        if !log.is_changed() && !screen.is_added() {
+            continue;
+        }
+        let mut lines = vec!["Quests (J: close)".to_string()];
+        for progress in &log.0 {
+            let Some(definition) = database.get(&progress.quest) else {
+                continue;
+            };
+            if progress.completed {
+                lines.push(format!("{} (completed)", definition.name));
+                continue;
+            }
+            let Some(stage) = definition.stages.get(progress.stage) else {
+                warn!("Quest {} has no stage {}", progress.quest, progress.stage);
+                continue;
+            };
+            lines.push(format!("{}: {}", definition.name, stage.description));
+            for (objective, count) in stage.objectives.iter().zip(&progress.objectives) {
+                lines.push(format!("  {}", objective_text(objective, *count, &items)));
+            }
+        }
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = lines.join("\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuestCompleted, QuestDatabase, QuestLog, QuestPlugin, QuestProgress, StartQuest};
+    use crate::area::AreaEntered;
+    use crate::combat::Defeated;
+    use crate::dialogue::DialogueStarted;
+    use crate::enemy::Enemy;
+    use crate::flags::WorldFlags;
+    use crate::inventory::{Inventory, InventoryPlugin};
+    use crate::item::{test_utils, ItemDatabase};
+    use crate::player::Player;
+    use crate::progression::ExperienceGained;
+    use crate::wallet::Wallet;
+    use bevy::prelude::*;
+
+    const QUESTS: &str = r#"[
+        (
+            id: "slimes",
+            name: "Slime trouble",
+            stages: [
+                (
+                    description: "Clear the meadow",
+                    objectives: [Kill(count: 2), Reach(1)],
+                ),
+                (
+                    description: "Bring a herb to the elder",
+                    objectives: [Collect(item: 0, count: 1), TalkTo("elder")],
+                ),
+            ],
+            rewards: [Currency(50), Experience(100), Flag("meadow_cleared")],
+        ),
+    ]"#;
+
+    fn get_test_app() -> (App, Entity) {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_event::<Defeated>();
+        app.add_event::<AreaEntered>();
+        app.add_event::<DialogueStarted>();
+        app.add_event::<ExperienceGained>();
+        app.init_resource::<WorldFlags>();
+        app.insert_resource(test_utils::test_database());
+        app.insert_resource(QuestDatabase::from_ron(QUESTS).unwrap());
+        app.add_plugins(InventoryPlugin);
+        app.add_plugins(QuestPlugin);
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(QuestLog::default())
+            .insert(Inventory::new(4))
+            .insert(Wallet::default())
+            .id();
+        app.world_mut().send_event(StartQuest {
+            quest: "slimes".to_string(),
+        });
+        app.update();
+        (app, player)
+    }
+
+    fn progress(app: &App, player: Entity) -> (usize, Vec<u32>) {
+        let log = app.world().get::<QuestLog>(player).unwrap();
+        let progress = log.get("slimes").unwrap();
+        (progress.stage, progress.objectives.clone())
+    }
+
+    #[test]
+    fn objectives_progress_through_stages() {
+        let (mut app, player) = get_test_app();
+        assert_eq!(progress(&app, player), (0, vec![0, 0]));
+
+        let enemy = app.world_mut().spawn(Enemy).id();
+        let bystander = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(Defeated { entity: enemy });
+        app.world_mut().send_event(Defeated { entity: bystander });
+        app.world_mut().send_event(AreaEntered(2.into()));
+        app.update();
+        assert_eq!(progress(&app, player), (0, vec![1, 0]));
+
+        app.world_mut().send_event(Defeated { entity: enemy });
+        app.world_mut().send_event(Defeated { entity: enemy });
+        app.world_mut().send_event(AreaEntered(1.into()));
+        app.world_mut().send_event(DialogueStarted {
+            tree: "elder".to_string(),
+        });
+        app.update();
+        assert_eq!(progress(&app, player), (1, vec![0, 0]));
+
+        let items = app.world().resource::<ItemDatabase>().clone();
+        app.world_mut()
+            .get_mut::<Inventory>(player)
+            .unwrap()
+            .add(&items, 0.into(), 1)
+            .unwrap();
+        app.update();
+        app.update();
+        assert_eq!(progress(&app, player), (1, vec![1, 0]));
+
+        app.world_mut().send_event(DialogueStarted {
+            tree: "elder".to_string(),
+        });
+        app.update();
+        assert!(app
+            .world()
+            .get::<QuestLog>(player)
+            .unwrap()
+            .is_completed("slimes"));
+        assert_eq!(app.world().get::<Wallet>(player), Some(&Wallet(50)));
+        assert!(app
+            .world()
+            .resource::<WorldFlags>()
+            .get_bool("meadow_cleared"));
+        let events = app.world().resource::<Events<QuestCompleted>>();
+        assert_eq!(events.get_reader().read(events).count(), 1);
+        let events = app.world().resource::<Events<ExperienceGained>>();
+        assert_eq!(events.get_reader().read(events).next().unwrap().amount, 100);
+    }
+
+    #[test]
+    fn invalid_stages_are_rejected() {
+        let empty = r#"[(id: "empty", name: "Empty", stages: [])]"#;
+        assert!(QuestDatabase::from_ron(empty).is_err());
+
+        // A saved stage that was removed from the data since.
+        let (mut app, player) = get_test_app();
+        app.world_mut().get_mut::<QuestLog>(player).unwrap().0 = vec![QuestProgress {
+            quest: "slimes".to_string(),
+            stage: 5,
+            objectives: vec![],
+            completed: false,
+        }];
+        app.world_mut().send_event(AreaEntered(1.into()));
+        app.update();
+        assert_eq!(progress(&app, player), (5, vec![]));
+    }
+}
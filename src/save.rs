@@ -0,0 +1,689 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::area::{
+    AreaIdentifier, AreaTransitionEvent, CurrentArea, GameAreas, PassageDestination,
+};
+use crate::clock::GameClock;
+use crate::combat::{apply_damage, Defeated, Health};
+use crate::crafting::KnownRecipes;
+use crate::equipment::Equipment;
+use crate::flags::{FlagStoragePlugin, WorldFlags};
+use crate::inventory::Inventory;
+use crate::map::VisitedAreas;
+use crate::migration::{MigrationError, Migrations};
+use crate::pickup::CollectedPickups;
+use crate::player::Player;
+use crate::progression::{Experience, Level};
+use crate::quest::QuestLog;
+use crate::state::is_playing;
+use crate::stats::Attributes;
+use crate::wallet::Wallet;
+
+pub struct SavePlugin;
+
+/// Version written to new save files. Bumping it requires registering a migration from the
+/// previous version in [`save_migrations`] and adding a fixture for it to the tests.
+pub const SAVE_VERSION: u32 = 4;
+
+/// Identifies an entity placed in the world whose removal, e.g. by being defeated, is saved.
+#[derive(
+    Clone, Component, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct PersistentId(pub usize);
+
+/// Persistent entities that have been removed from the world. Matching entities are despawned
+/// whenever this changes, e.g. after loading. Entities are never respawned, so loading a save made
+/// before an entity was removed doesn't bring it back.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Resource, Serialize)]
+pub struct RemovedEntities(pub HashSet<PersistentId>);
+
+#[derive(Clone, Debug, Resource)]
+pub struct SaveSettings {
+    pub directory: PathBuf,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        SaveSettings {
+            directory: PathBuf::from("saves"),
+        }
+    }
+}
+
+impl SaveSettings {
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.directory.join(format!("slot{slot}.json"))
+    }
+
+    pub fn autosave_path(&self, slot: usize) -> PathBuf {
+        self.directory.join(format!("autosave{slot}.json"))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerState {
+    pub transform: Transform,
+    pub health: Health,
+    pub attributes: Attributes,
+    pub experience: Experience,
+    pub level: Level,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    pub wallet: Wallet,
+    pub known_recipes: KnownRecipes,
+    pub quests: QuestLog,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WorldState {
+    pub collected_pickups: CollectedPickups,
+    pub removed_entities: RemovedEntities,
+    pub flags: WorldFlags,
+    pub visited_areas: VisitedAreas,
+    pub clock: GameClock,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub area: AreaIdentifier,
+    pub player: PlayerState,
+    pub world: WorldState,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Migration(MigrationError),
+    NoPlayer,
+    UnknownArea(AreaIdentifier),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => error.fmt(f),
+            SaveError::Format(error) => write!(f, "invalid save file: {error}"),
+            SaveError::Migration(error) => write!(f, "couldn't upgrade save file: {error}"),
+            SaveError::NoPlayer => write!(f, "there is no player to save or load"),
+            SaveError::UnknownArea(area) => write!(f, "area {} doesn't exist", area.0),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Format(error)
+    }
+}
+
+impl From<MigrationError> for SaveError {
+    fn from(error: MigrationError) -> Self {
+        SaveError::Migration(error)
+    }
+}
+
+/// Version 2 moved the persisted world resources into a `world` object.
+fn group_world_state(document: &mut Value) -> Result<(), String> {
+    let document = document.as_object_mut().ok_or("save isn't an object")?;
+    let mut world = Map::new();
+    for key in ["collected_pickups", "removed_entities", "flags"] {
+        let value = document.remove(key).ok_or(format!("missing {key}"))?;
+        world.insert(key.to_string(), value);
+    }
+    document.insert("world".to_string(), Value::Object(world));
+    Ok(())
+}
+
+/// Version 3 added visited areas, of which older saves only know the one they were made in.
+fn add_visited_areas(document: &mut Value) -> Result<(), String> {
+    let area = document.get("area").cloned().ok_or("missing area")?;
+    let world = document
+        .get_mut("world")
+        .and_then(Value::as_object_mut)
+        .ok_or("missing world")?;
+    world.insert("visited_areas".to_string(), Value::Array(vec![area]));
+    Ok(())
+}
+
+/// Version 4 added the in-game clock, which older saves start at its default time.
+fn add_clock(document: &mut Value) -> Result<(), String> {
+    let world = document
+        .get_mut("world")
+        .and_then(Value::as_object_mut)
+        .ok_or("missing world")?;
+    let clock = serde_json::to_value(GameClock::default()).map_err(|error| error.to_string())?;
+    world.insert("clock".to_string(), clock);
+    Ok(())
+}
+
+pub fn save_migrations() -> Migrations {
+    Migrations::new(SAVE_VERSION)
+        .with_migration(1, group_world_state)
+        .with_migration(2, add_visited_areas)
+        .with_migration(3, add_clock)
+}
+
+/// Migrations applied to save files when they are loaded.
+#[derive(Clone, Debug, Resource)]
+pub struct SaveMigrations(pub Migrations);
+
+impl Default for SaveMigrations {
+    fn default() -> Self {
+        SaveMigrations(save_migrations())
+    }
+}
+
+impl SaveData {
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a save file, upgrading it from older versions first.
+    pub fn from_json(source: &str, migrations: &Migrations) -> Result<Self, SaveError> {
+        let mut document: Value = serde_json::from_str(source)?;
+        migrations.migrate(&mut document)?;
+        Ok(serde_json::from_value(document)?)
+    }
+
+    pub fn write(&self, settings: &SaveSettings, slot: usize) -> Result<(), SaveError> {
+        self.write_to(&settings.slot_path(slot))
+    }
+
+    pub fn read(
+        settings: &SaveSettings,
+        slot: usize,
+        migrations: &Migrations,
+    ) -> Result<Self, SaveError> {
+        SaveData::read_from(&settings.slot_path(slot), migrations)
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn read_from(path: &Path, migrations: &Migrations) -> Result<Self, SaveError> {
+        SaveData::from_json(&fs::read_to_string(path)?, migrations)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct SaveGame {
+    pub slot: usize,
+}
+
+/// Restores the player and world state from a slot. See [`RemovedEntities`] for what can't be
+/// restored.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct LoadGame {
+    pub slot: usize,
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct GameSaved {
+    pub slot: usize,
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct GameLoaded {
+    pub slot: usize,
+}
+
+pub const QUICK_SAVE_SLOT: usize = 0;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FlagStoragePlugin>() {
+            app.add_plugins(FlagStoragePlugin);
+        }
+        app.init_resource::<SaveSettings>()
+            .init_resource::<SaveMigrations>()
+            .init_resource::<RemovedEntities>()
+            .init_resource::<CollectedPickups>()
+            .init_resource::<VisitedAreas>()
+            .init_resource::<GameClock>()
+            .add_event::<SaveGame>()
+            .add_event::<LoadGame>()
+            .add_event::<GameSaved>()
+            .add_event::<GameLoaded>()
+            .add_systems(Update, record_removed_entities.after(apply_damage))
+            .add_systems(Update, quick_save.run_if(is_playing))
+            .add_systems(Update, save_game.after(quick_save))
+            .add_systems(Update, load_game.after(save_game))
+            .add_systems(
+                Update,
+                despawn_removed_entities
+                    .after(record_removed_entities)
+                    .after(load_game)
+                    .run_if(resource_changed::<RemovedEntities>),
+            );
+    }
+}
+
+fn record_removed_entities(
+    mut ev_defeated: EventReader<Defeated>,
+    persistent_query: Query<&PersistentId>,
+    mut removed: ResMut<RemovedEntities>,
+) {
+    for defeated in ev_defeated.read() {
+        if let Ok(id) = persistent_query.get(defeated.entity) {
+            removed.0.insert(*id);
+        }
+    }
+}
+
+fn despawn_removed_entities(
+    mut commands: Commands,
+    removed: Res<RemovedEntities>,
+    persistent_query: Query<(Entity, &PersistentId)>,
+) {
+    for (entity, id) in persistent_query.iter() {
+        if removed.0.contains(id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn quick_save(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut ev_save: EventWriter<SaveGame>,
+    mut ev_load: EventWriter<LoadGame>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        ev_save.send(SaveGame {
+            slot: QUICK_SAVE_SLOT,
+        });
+    }
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        ev_load.send(LoadGame {
+            slot: QUICK_SAVE_SLOT,
+        });
+    }
+}
+
+pub(crate) type PlayerComponents<'a> = (
+    &'a mut Transform,
+    &'a mut Health,
+    &'a mut Attributes,
+    &'a mut Experience,
+    &'a mut Level,
+    &'a mut Inventory,
+    &'a mut Equipment,
+    &'a mut Wallet,
+    &'a mut KnownRecipes,
+    &'a mut QuestLog,
+);
+
+/// Persisted world resources, shared by saving and loading.
+pub(crate) type WorldResources<'a> = (
+    ResMut<'a, CollectedPickups>,
+    ResMut<'a, RemovedEntities>,
+    ResMut<'a, WorldFlags>,
+    ResMut<'a, VisitedAreas>,
+    ResMut<'a, GameClock>,
+);
+
+pub(crate) fn capture(
+    area: AreaIdentifier,
+    player_query: &Query<PlayerComponents, With<Player>>,
+    (collected_pickups, removed_entities, flags, visited_areas, clock): &WorldResources,
+) -> Result<SaveData, SaveError> {
+    let (
+        transform,
+        health,
+        attributes,
+        experience,
+        level,
+        inventory,
+        equipment,
+        wallet,
+        known_recipes,
+        quests,
+    ) = player_query.get_single().map_err(|_| SaveError::NoPlayer)?;
+    Ok(SaveData {
+        version: SAVE_VERSION,
+        area,
+        player: PlayerState {
+            transform: *transform,
+            health: *health,
+            attributes: *attributes,
+            experience: *experience,
+            level: *level,
+            inventory: inventory.clone(),
+            equipment: equipment.clone(),
+            wallet: *wallet,
+            known_recipes: known_recipes.clone(),
+            quests: quests.clone(),
+        },
+        world: WorldState {
+            collected_pickups: (*collected_pickups).clone(),
+            removed_entities: (*removed_entities).clone(),
+            flags: (*flags).clone(),
+            visited_areas: (*visited_areas).clone(),
+            clock: **clock,
+        },
+    })
+}
+
+/// Applies saved state to the player and world resources, leaving the loaded area as it is.
+pub(crate) fn restore(
+    data: SaveData,
+    player_query: &mut Query<PlayerComponents, With<Player>>,
+    world_resources: &mut WorldResources,
+) -> Result<(), SaveError> {
+    restore_player(data.player, player_query)?;
+    restore_world(data.world, world_resources);
+    Ok(())
+}
+
+pub(crate) fn restore_player(
+    player: PlayerState,
+    player_query: &mut Query<PlayerComponents, With<Player>>,
+) -> Result<(), SaveError> {
+    let (
+        mut transform,
+        mut health,
+        mut attributes,
+        mut experience,
+        mut level,
+        mut inventory,
+        mut equipment,
+        mut wallet,
+        mut known_recipes,
+        mut quests,
+    ) = player_query
+        .get_single_mut()
+        .map_err(|_| SaveError::NoPlayer)?;
+    *transform = player.transform;
+    *health = player.health;
+    *attributes = player.attributes;
+    *experience = player.experience;
+    *level = player.level;
+    *inventory = player.inventory;
+    *equipment = player.equipment;
+    *wallet = player.wallet;
+    *known_recipes = player.known_recipes;
+    *quests = player.quests;
+    Ok(())
+}
+
+pub(crate) fn restore_world(
+    world: WorldState,
+    (collected_pickups, removed_entities, flags, visited_areas, clock): &mut WorldResources,
+) {
+    **collected_pickups = world.collected_pickups;
+    **removed_entities = world.removed_entities;
+    **flags = world.flags;
+    **visited_areas = world.visited_areas;
+    **clock = world.clock;
+}
+
+fn save_game(
+    mut ev_save: EventReader<SaveGame>,
+    settings: Res<SaveSettings>,
+    current_area: Option<Res<CurrentArea>>,
+    player_query: Query<PlayerComponents, With<Player>>,
+    world_resources: WorldResources,
+    mut ev_saved: EventWriter<GameSaved>,
+) {
+    let area = current_area.map_or(AreaIdentifier(0), |current_area| current_area.0);
+    for save in ev_save.read() {
+        let result = capture(area, &player_query, &world_resources)
+            .and_then(|data| data.write(&settings, save.slot));
+        match result {
+            Ok(()) => {
+                ev_saved.send(GameSaved { slot: save.slot });
+            }
+            Err(error) => error!("Couldn't save to slot {}: {error}", save.slot),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn load_game(
+    mut ev_load: EventReader<LoadGame>,
+    settings: Res<SaveSettings>,
+    migrations: Res<SaveMigrations>,
+    game_areas: Option<Res<GameAreas>>,
+    mut player_query: Query<PlayerComponents, With<Player>>,
+    mut world_resources: WorldResources,
+    mut ev_area_transition: EventWriter<AreaTransitionEvent>,
+    mut ev_loaded: EventWriter<GameLoaded>,
+) {
+    let Some(load) = ev_load.read().last() else {
+        return;
+    };
+    let data =
+        SaveData::read(&settings, load.slot, &migrations.0).and_then(|data| match &game_areas {
+            Some(game_areas) if game_areas.get(data.area).is_none() => {
+                Err(SaveError::UnknownArea(data.area))
+            }
+            _ => Ok(data),
+        });
+    let data = match data {
+        Ok(data) => data,
+        Err(error) => {
+            error!("Couldn't load slot {}: {error}", load.slot);
+            return;
+        }
+    };
+    let destination = PassageDestination(data.area, data.player.transform);
+    if let Err(error) = restore(data, &mut player_query, &mut world_resources) {
+        error!("Couldn't load slot {}: {error}", load.slot);
+        return;
+    }
+    // Reloading the area goes through the same path as walking through a passage.
+    ev_area_transition.send(AreaTransitionEvent(destination));
+    ev_loaded.send(GameLoaded { slot: load.slot });
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::path::PathBuf;
+
+    /// Empty directory for save files that is unique to the test and process.
+    pub fn temp_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("rpg_system_2d_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        directory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::temp_directory;
+    use super::{
+        save_migrations, LoadGame, PersistentId, RemovedEntities, SaveData, SaveError, SaveGame,
+        SavePlugin, SaveSettings, SAVE_VERSION,
+    };
+    use crate::area::{
+        Area, AreaIdentifier, AreaTransitionEvent, CurrentArea, GameAreas, PassageDestination,
+    };
+    use crate::combat::{Defeated, Health};
+    use crate::crafting::KnownRecipes;
+    use crate::equipment::Equipment;
+    use crate::flags::{FlagScope, FlagValue, WorldFlags};
+    use crate::inventory::Inventory;
+    use crate::item::test_utils;
+    use crate::migration::MigrationError;
+    use crate::pickup::{CollectedPickups, PickupId};
+    use crate::player::Player;
+    use crate::progression::{Experience, Level};
+    use crate::quest::QuestLog;
+    use crate::stats::Attributes;
+    use crate::wallet::Wallet;
+    use bevy::prelude::*;
+    use std::path::PathBuf;
+
+    fn get_test_app(directory: PathBuf) -> (App, Entity) {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_event::<Defeated>();
+        app.add_event::<AreaTransitionEvent>();
+        app.insert_resource(SaveSettings { directory });
+        app.insert_resource(CurrentArea(1.into()));
+        app.add_plugins(SavePlugin);
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(Transform::from_xyz(10., 20., 0.))
+            .insert(Health::new(80.))
+            .insert(Attributes::default())
+            .insert(Experience(0))
+            .insert(Level(1))
+            .insert(Inventory::new(4))
+            .insert(Equipment::default())
+            .insert(Wallet(0))
+            .insert(KnownRecipes::default())
+            .insert(QuestLog::default())
+            .id();
+        (app, player)
+    }
+
+    #[test]
+    fn saving_and_loading_restores_state() {
+        let directory = temp_directory("save");
+        let (mut app, player) = get_test_app(directory.clone());
+        let enemy = app.world_mut().spawn(PersistentId(7)).id();
+        app.world_mut().send_event(Defeated { entity: enemy });
+        app.update();
+        assert!(app.world().get_entity(enemy).is_none());
+
+        let database = test_utils::test_database();
+        app.world_mut()
+            .get_mut::<Inventory>(player)
+            .unwrap()
+            .add(&database, 0.into(), 3)
+            .unwrap();
+        app.world_mut().get_mut::<Wallet>(player).unwrap().0 = 42;
+        app.world_mut().get_mut::<Experience>(player).unwrap().0 = 150;
+        app.world_mut()
+            .resource_mut::<WorldFlags>()
+            .set_bool("met_elder", true);
+        app.world_mut().resource_mut::<WorldFlags>().set(
+            FlagScope::Area(1.into()),
+            "lever",
+            FlagValue::Int(2),
+        );
+        app.world_mut()
+            .resource_mut::<CollectedPickups>()
+            .0
+            .insert(PickupId(3));
+        app.world_mut().send_event(SaveGame { slot: 2 });
+        app.update();
+        assert!(directory.join("slot2.json").exists());
+
+        app.world_mut()
+            .entity_mut(player)
+            .insert(Transform::default())
+            .insert(Inventory::new(4))
+            .insert(Wallet(0))
+            .insert(Experience(0));
+        app.insert_resource(WorldFlags::default());
+        app.insert_resource(CollectedPickups::default());
+        app.insert_resource(RemovedEntities::default());
+        let respawned = app.world_mut().spawn(PersistentId(7)).id();
+        app.world_mut().send_event(LoadGame { slot: 2 });
+        app.update();
+        app.update();
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<Transform>(player),
+            Some(&Transform::from_xyz(10., 20., 0.))
+        );
+        assert_eq!(world.get::<Inventory>(player).unwrap().count(0.into()), 3);
+        assert_eq!(world.get::<Wallet>(player), Some(&Wallet(42)));
+        assert_eq!(world.get::<Experience>(player), Some(&Experience(150)));
+        assert!(world.resource::<WorldFlags>().get_bool("met_elder"));
+        assert_eq!(
+            world
+                .resource::<WorldFlags>()
+                .get(FlagScope::Area(1.into()), "lever"),
+            Some(&FlagValue::Int(2))
+        );
+        assert!(world
+            .resource::<CollectedPickups>()
+            .0
+            .contains(&PickupId(3)));
+        assert!(world.get_entity(respawned).is_none());
+        let events = world.resource::<Events<AreaTransitionEvent>>();
+        assert_eq!(
+            events.get_reader().read(events).last(),
+            Some(&AreaTransitionEvent(PassageDestination(
+                AreaIdentifier(1),
+                Transform::from_xyz(10., 20., 0.)
+            )))
+        );
+
+        // Saves naming an area that no longer exists are rejected without touching the player.
+        app.insert_resource(GameAreas::new(vec![Area::new(Color::BLACK, vec![])]));
+        app.world_mut().get_mut::<Wallet>(player).unwrap().0 = 0;
+        app.world_mut().send_event(LoadGame { slot: 2 });
+        app.update();
+        assert_eq!(app.world().get::<Wallet>(player), Some(&Wallet(0)));
+        let _ = std::fs::remove_dir_all(directory);
+    }
+
+    /// Save files written by every version, oldest first.
+    const FIXTURES: [(u32, &str); 4] = [
+        (1, include_str!("../fixtures/saves/v1.json")),
+        (2, include_str!("../fixtures/saves/v2.json")),
+        (3, include_str!("../fixtures/saves/v3.json")),
+        (4, include_str!("../fixtures/saves/v4.json")),
+    ];
+
+    #[test]
+    fn fixtures_of_every_version_load() {
+        assert_eq!(FIXTURES.last().unwrap().0, SAVE_VERSION);
+        let migrations = save_migrations();
+        for (version, source) in FIXTURES {
+            let data = SaveData::from_json(source, &migrations)
+                .unwrap_or_else(|error| panic!("version {version}: {error}"));
+            assert_eq!(data.version, SAVE_VERSION);
+            assert_eq!(data.area, AreaIdentifier(1));
+            assert_eq!(data.player.wallet, Wallet(42));
+            assert_eq!(data.player.inventory.count(0.into()), 3);
+            assert!(data.player.quests.get("slimes").is_some());
+            assert!(data.world.flags.get_bool("met_elder"));
+            assert_eq!(
+                data.world.flags.get(FlagScope::Area(1.into()), "lever"),
+                Some(&FlagValue::Int(2))
+            );
+            assert!(data.world.collected_pickups.0.contains(&PickupId(3)));
+            assert!(data.world.removed_entities.0.contains(&PersistentId(7)));
+            assert!(data.world.visited_areas.0.contains(&AreaIdentifier(1)));
+        }
+
+        let future = FIXTURES[0]
+            .1
+            .replacen("\"version\": 1", "\"version\": 99", 1);
+        assert!(matches!(
+            SaveData::from_json(&future, &migrations),
+            Err(SaveError::Migration(MigrationError::UnsupportedVersion(99)))
+        ));
+    }
+}
@@ -0,0 +1,482 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::area::{AreaIdentifier, CurrentArea};
+use crate::interaction::{Interactable, Interacted};
+use crate::inventory::{Inventory, InventoryError};
+use crate::item::{ItemDatabase, ItemId};
+use crate::player::Player;
+use crate::wallet::Wallet;
+
+pub struct ShopPlugin;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StockEntry {
+    pub item: ItemId,
+    /// Price the player pays per item.
+    pub buy_price: u32,
+    /// Price the merchant pays per item.
+    pub sell_price: u32,
+    pub quantity: u32,
+    /// Quantity the entry is refilled to on restocking.
+    pub max_quantity: u32,
+}
+
+impl StockEntry {
+    pub fn new(item: ItemId, buy_price: u32, sell_price: u32, quantity: u32) -> Self {
+        StockEntry {
+            item,
+            buy_price,
+            sell_price,
+            quantity,
+            max_quantity: quantity,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShopError {
+    NotTraded(ItemId),
+    OutOfStock,
+    InsufficientFunds,
+    Inventory(InventoryError),
+}
+
+impl fmt::Display for ShopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShopError::NotTraded(item) => write!(f, "merchant doesn't trade item {}", item.0),
+            ShopError::OutOfStock => write!(f, "merchant is out of stock"),
+            ShopError::InsufficientFunds => write!(f, "not enough money"),
+            ShopError::Inventory(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ShopError {}
+
+impl From<InventoryError> for ShopError {
+    fn from(error: InventoryError) -> Self {
+        ShopError::Inventory(error)
+    }
+}
+
+#[derive(Clone, Component, Debug)]
+pub struct Merchant {
+    pub stock: Vec<StockEntry>,
+    pub restock: Timer,
+}
+
+impl Merchant {
+    pub fn new(stock: Vec<StockEntry>, restock_seconds: f32) -> Self {
+        Merchant {
+            stock,
+            restock: Timer::from_seconds(restock_seconds, TimerMode::Repeating),
+        }
+    }
+
+    fn entry_mut(&mut self, item: ItemId) -> Result<&mut StockEntry, ShopError> {
+        self.stock
+            .iter_mut()
+            .find(|entry| entry.item == item)
+            .ok_or(ShopError::NotTraded(item))
+    }
+
+    pub fn buy(
+        &mut self,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+        database: &ItemDatabase,
+        item: ItemId,
+        count: u32,
+    ) -> Result<(), ShopError> {
+        let entry = self.entry_mut(item)?;
+        if entry.quantity < count {
+            return Err(ShopError::OutOfStock);
+        }
+        let price = entry.buy_price.saturating_mul(count);
+        if wallet.0 < price {
+            return Err(ShopError::InsufficientFunds);
+        }
+        inventory.add(database, item, count)?;
+        wallet.withdraw(price);
+        entry.quantity -= count;
+        Ok(())
+    }
+
+    pub fn sell(
+        &mut self,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+        item: ItemId,
+        count: u32,
+    ) -> Result<(), ShopError> {
+        let entry = self.entry_mut(item)?;
+        inventory.remove(item, count)?;
+        wallet.deposit(entry.sell_price.saturating_mul(count));
+        entry.quantity += count;
+        Ok(())
+    }
+
+    pub fn restock(&mut self) {
+        for entry in &mut self.stock {
+            entry.quantity = entry.quantity.max(entry.max_quantity);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub struct OpenShop {
+    pub merchant: Entity,
+}
+
+/// Merchant whose shop screen is open.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct ActiveShop(pub Entity);
+
+#[derive(Component)]
+struct ShopScreen;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OpenShop>()
+            .add_systems(Update, restock_merchants)
+            .add_systems(Update, merchant_interaction)
+            .add_systems(Update, open_shop.after(merchant_interaction))
+            .add_systems(
+                Update,
+                (shop_input, shop_screen_text, leave_shop)
+                    .chain()
+                    .after(open_shop)
+                    .run_if(resource_exists::<ActiveShop>),
+            );
+    }
+}
+
+fn restock_merchants(time: Res<Time>, mut merchant_query: Query<&mut Merchant>) {
+    for mut merchant in merchant_query.iter_mut() {
+        if merchant.restock.tick(time.delta()).just_finished() {
+            merchant.restock();
+        }
+    }
+}
+
+fn merchant_interaction(
+    mut ev_interacted: EventReader<Interacted>,
+    merchant_query: Query<(), With<Merchant>>,
+    mut ev_open_shop: EventWriter<OpenShop>,
+) {
+    for interacted in ev_interacted.read() {
+        if merchant_query.contains(interacted.entity) {
+            ev_open_shop.send(OpenShop {
+                merchant: interacted.entity,
+            });
+        }
+    }
+}
+
+fn open_shop(
+    mut commands: Commands,
+    mut ev_open_shop: EventReader<OpenShop>,
+    active_shop: Option<Res<ActiveShop>>,
+) {
+    let Some(open) = ev_open_shop.read().last() else {
+        return;
+    };
+    if active_shop.is_some() {
+        return;
+    }
+    commands.insert_resource(ActiveShop(open.merchant));
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(40.),
+                top: Val::Px(40.),
+                padding: UiRect::all(Val::Px(16.)),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.8).into(),
+            ..default()
+        })
+        .insert(ShopScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.,
+                    ..default()
+                },
+            ));
+        });
+}
+
+const STOCK_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+fn shop_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    active_shop: Res<ActiveShop>,
+    database: Res<ItemDatabase>,
+    mut merchant_query: Query<&mut Merchant>,
+    mut player_query: Query<(&mut Wallet, &mut Inventory), With<Player>>,
+    screen_query: Query<Entity, With<ShopScreen>>,
+) {
+    let (Ok(mut merchant), Ok((mut wallet, mut inventory))) = (
+        merchant_query.get_mut(active_shop.0),
+        player_query.get_single_mut(),
+    ) else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        close_shop(&mut commands, &screen_query);
+        return;
+    }
+    let selling = keyboard_input.pressed(KeyCode::ShiftLeft);
+    for (key, i) in STOCK_KEYS.iter().zip(0..merchant.stock.len()) {
+        if !keyboard_input.just_pressed(*key) {
+            continue;
+        }
+        let item = merchant.stock[i].item;
+        let result = if selling {
+            merchant.sell(&mut wallet, &mut inventory, item, 1)
+        } else {
+            merchant.buy(&mut wallet, &mut inventory, &database, item, 1)
+        };
+        if let Err(error) = result {
+            info!("Trade failed: {error}");
+        }
+    }
+}
+
+fn close_shop(commands: &mut Commands, screen_query: &Query<Entity, With<ShopScreen>>) {
+    commands.remove_resource::<ActiveShop>();
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+type MerchantPlacement<'a> = (
+    &'a Transform,
+    Option<&'a Interactable>,
+    Option<&'a AreaIdentifier>,
+);
+
+// The shop closes once the player walks out of the merchant's reach or area, or the merchant is
+// gone.
+fn leave_shop(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    active_shop: Res<ActiveShop>,
+    current_area: Option<Res<CurrentArea>>,
+    merchant_query: Query<MerchantPlacement, With<Merchant>>,
+    player_query: Query<&Transform, With<Player>>,
+    screen_query: Query<Entity, With<ShopScreen>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    let in_reach = match (merchant_query.get(active_shop.0), player_query.get_single()) {
+        (Ok((merchant, interactable, area)), Ok(player)) => {
+            let same_area = match (area, &current_area) {
+                (Some(area), Some(current_area)) => *area == current_area.0,
+                _ => true,
+            };
+            same_area
+                && interactable.is_none_or(|interactable| {
+                    merchant
+                        .translation
+                        .truncate()
+                        .distance(player.translation.truncate())
+                        <= interactable.radius
+                })
+        }
+        _ => false,
+    };
+    if !in_reach {
+        close_shop(&mut commands, &screen_query);
+    }
+}
+
+fn shop_screen_text(
+    active_shop: Res<ActiveShop>,
+    database: Res<ItemDatabase>,
+    merchant_query: Query<&Merchant>,
+    wallet_query: Query<&Wallet, With<Player>>,
+    screen_query: Query<&Children, With<ShopScreen>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let (Ok(merchant), Ok(wallet)) = (merchant_query.get(active_shop.0), wallet_query.get_single())
+    else {
+        return;
+    };
+    let mut lines: Vec<String> = merchant
+        .stock
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let name = database
+                .get(entry.item)
+                .map_or("?", |definition| definition.name.as_str());
+            format!(
+                "{}: {} x{}  buy {}  sell {}",
+                i + 1,
+                name,
+                entry.quantity,
+                entry.buy_price,
+                entry.sell_price
+            )
+        })
+        .collect();
+    lines.push(format!("Money: {}", wallet.0));
+    lines.push("Number: buy  Shift+number: sell  Esc: leave".to_string());
+    for children in screen_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = lines.join("\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActiveShop, Merchant, OpenShop, ShopError, ShopPlugin, StockEntry};
+    use crate::area::{AreaIdentifier, CurrentArea};
+    use crate::interaction::{Interactable, Interacted};
+    use crate::inventory::{Inventory, InventoryError};
+    use crate::item::test_utils::material;
+    use crate::item::{ItemDatabase, ItemId};
+    use crate::player::Player;
+    use crate::wallet::Wallet;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const HERB: ItemId = ItemId(0);
+    const ROCK: ItemId = ItemId(1);
+
+    fn get_test_database() -> ItemDatabase {
+        ItemDatabase::new(vec![material(HERB, "Herb"), material(ROCK, "Rock")])
+    }
+
+    #[test]
+    fn buying_and_selling() {
+        let database = get_test_database();
+        let mut merchant = Merchant::new(vec![StockEntry::new(HERB, 10, 4, 3)], 60.);
+        let mut wallet = Wallet(25);
+        let mut inventory = Inventory::new(1);
+
+        merchant
+            .buy(&mut wallet, &mut inventory, &database, HERB, 2)
+            .unwrap();
+        assert_eq!(wallet, Wallet(5));
+        assert_eq!(inventory.count(HERB), 2);
+        assert_eq!(merchant.stock[0].quantity, 1);
+        assert_eq!(
+            merchant.buy(&mut wallet, &mut inventory, &database, HERB, 1),
+            Err(ShopError::InsufficientFunds)
+        );
+        assert_eq!(
+            merchant.buy(&mut wallet, &mut inventory, &database, HERB, 2),
+            Err(ShopError::OutOfStock)
+        );
+        assert_eq!(
+            merchant.buy(&mut wallet, &mut inventory, &database, ROCK, 1),
+            Err(ShopError::NotTraded(ROCK))
+        );
+
+        merchant.sell(&mut wallet, &mut inventory, HERB, 1).unwrap();
+        assert_eq!(wallet, Wallet(9));
+        assert_eq!(merchant.stock[0].quantity, 2);
+        assert_eq!(
+            merchant.sell(&mut wallet, &mut inventory, HERB, 5),
+            Err(ShopError::Inventory(InventoryError::NotEnoughItems))
+        );
+    }
+
+    #[test]
+    fn merchants_restock() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+        app.add_systems(Update, super::restock_merchants);
+        let mut merchant = Merchant::new(vec![StockEntry::new(HERB, 10, 4, 3)], 1.);
+        merchant.stock[0].quantity = 0;
+        let entity = app.world_mut().spawn(merchant).id();
+        for _ in 0..6 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().get::<Merchant>(entity).unwrap().stock[0].quantity,
+            3
+        );
+    }
+
+    #[test]
+    fn shop_closes_when_player_walks_away() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_event::<Interacted>();
+        app.insert_resource(get_test_database());
+        app.insert_resource(CurrentArea(0.into()));
+        app.add_plugins(ShopPlugin);
+        let merchant = app
+            .world_mut()
+            .spawn(Merchant::new(vec![StockEntry::new(HERB, 10, 4, 3)], 60.))
+            .insert(Interactable::new("Trade", 80.))
+            .insert(Transform::default())
+            .insert(AreaIdentifier(0))
+            .id();
+        let player = app
+            .world_mut()
+            .spawn(Player::default())
+            .insert(Transform::from_xyz(50., 0., 0.))
+            .insert(Wallet(0))
+            .insert(Inventory::new(1))
+            .id();
+        app.world_mut().send_event(OpenShop { merchant });
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world().get_resource::<ActiveShop>(),
+            Some(&ActiveShop(merchant))
+        );
+
+        app.world_mut()
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation
+            .x = 200.;
+        app.update();
+        assert!(app.world().get_resource::<ActiveShop>().is_none());
+
+        // Arriving at the same spot in another area also closes it.
+        app.world_mut()
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation
+            .x = 50.;
+        app.world_mut().send_event(OpenShop { merchant });
+        app.update();
+        app.update();
+        assert!(app.world().get_resource::<ActiveShop>().is_some());
+        app.insert_resource(CurrentArea(1.into()));
+        app.update();
+        assert!(app.world().get_resource::<ActiveShop>().is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::asset::RecursiveDependencyLoadState;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::combat::Defeated;
+use crate::player::Player;
+
+pub struct GameStatePlugin;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    /// Waits for [`LoadingAssets`] before play starts.
+    Loading,
+    Playing,
+    Paused,
+    Battle,
+    Dialogue,
+    GameOver,
+}
+
+/// Run condition for overworld systems. Apps that don't add [`GameStatePlugin`] are always
+/// considered to be playing.
+pub fn is_playing(state: Option<Res<State<GameState>>>) -> bool {
+    state.is_none_or(|state| *state.get() == GameState::Playing)
+}
+
+/// Assets that must be loaded, or have failed to, before leaving [`GameState::Loading`].
+#[derive(Clone, Debug, Default, Resource)]
+pub struct LoadingAssets(pub Vec<UntypedHandle>);
+
+impl LoadingAssets {
+    pub fn add(&mut self, handle: impl Into<UntypedHandle>) {
+        self.0.push(handle.into());
+    }
+}
+
+// Game time stands still outside of play, so buffs, cooldowns and timers don't run out in menus,
+// battles or dialogue.
+fn sync_pause(
+    state: Res<State<GameState>>,
+    mut config: ResMut<RapierConfiguration>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let playing = *state.get() == GameState::Playing;
+    config.physics_pipeline_active = playing;
+    if playing {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
+fn finish_loading(
+    asset_server: Option<Res<AssetServer>>,
+    loading: Res<LoadingAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let loaded = asset_server.is_none_or(|asset_server| {
+        loading.0.iter().all(|handle| {
+            matches!(
+                asset_server.get_recursive_dependency_load_state(handle.id()),
+                Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed)
+            )
+        })
+    });
+    if loaded {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn game_over(
+    mut ev_defeated: EventReader<Defeated>,
+    player_query: Query<(), With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if ev_defeated
+        .read()
+        .any(|defeated| player_query.contains(defeated.entity))
+    {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .init_resource::<LoadingAssets>()
+            .add_event::<Defeated>()
+            .add_systems(Update, sync_pause.run_if(state_changed::<GameState>))
+            .add_systems(Update, finish_loading.run_if(in_state(GameState::Loading)))
+            .add_systems(PostUpdate, game_over);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameState, GameStatePlugin};
+    use crate::combat::Defeated;
+    use crate::player::Player;
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::plugin::{NoUserData, RapierConfiguration, RapierPhysicsPlugin};
+
+    fn state(app: &App) -> GameState {
+        *app.world().resource::<State<GameState>>().get()
+    }
+
+    fn game_running(app: &App) -> bool {
+        let time_running = !app.world().resource::<Time<Virtual>>().is_paused();
+        let physics_running = app
+            .world()
+            .resource::<RapierConfiguration>()
+            .physics_pipeline_active;
+        assert_eq!(time_running, physics_running);
+        physics_running
+    }
+
+    #[test]
+    fn physics_and_time_only_run_while_playing() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        app.add_plugins(GameStatePlugin);
+        let player = app.world_mut().spawn(Player::default()).id();
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+        assert!(!game_running(&app));
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Loading);
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Playing);
+        assert!(game_running(&app));
+
+        app.world_mut().send_event(Defeated { entity: player });
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::GameOver);
+        assert!(!game_running(&app));
+    }
+}
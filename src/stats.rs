@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::combat::Health;
+
+pub struct StatsPlugin;
+
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Attributes {
+    pub strength: f32,
+    pub dexterity: f32,
+    pub intelligence: f32,
+    pub vitality: f32,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes {
+            strength: 10.,
+            dexterity: 10.,
+            intelligence: 10.,
+            vitality: 10.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Stat {
+    MaxHealth,
+    Attack,
+    Defense,
+    MoveSpeed,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ModifierKind {
+    Flat(f32),
+    /// Fraction of the stat added on top, e.g. `0.25` for +25%.
+    Percent(f32),
+}
+
+/// Where a modifier came from, so it can be removed again. The identifier is chosen by whatever
+/// applies the modifier.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ModifierSource {
+    Equipment(usize),
+    Buff(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatModifier {
+    pub source: ModifierSource,
+    pub stat: Stat,
+    pub kind: ModifierKind,
+}
+
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub struct StatModifiers(Vec<StatModifier>);
+
+impl StatModifiers {
+    pub fn add(&mut self, modifier: StatModifier) {
+        self.0.push(modifier);
+    }
+
+    pub fn remove_source(&mut self, source: ModifierSource) {
+        self.0.retain(|modifier| modifier.source != source);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatModifier> {
+        self.0.iter()
+    }
+}
+
+/// Modifiers that are removed again once their timer finishes.
+#[derive(Clone, Component, Debug, Default)]
+pub struct TimedModifiers(Vec<(ModifierSource, Timer)>);
+
+impl TimedModifiers {
+    /// Applies the modifier, replacing any earlier modifiers from the same source.
+    pub fn apply(&mut self, modifiers: &mut StatModifiers, modifier: StatModifier, duration: f32) {
+        modifiers.remove_source(modifier.source);
+        modifiers.add(modifier);
+        self.0.retain(|(source, _)| *source != modifier.source);
+        self.0.push((
+            modifier.source,
+            Timer::from_seconds(duration, TimerMode::Once),
+        ));
+    }
+}
+
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq)]
+pub struct DerivedStats {
+    pub max_health: f32,
+    pub attack: f32,
+    pub defense: f32,
+    pub move_speed: f32,
+}
+
+impl DerivedStats {
+    pub fn compute(attributes: &Attributes, modifiers: &StatModifiers) -> Self {
+        let apply = |stat: Stat, base: f32| {
+            let (flat, percent) = modifiers
+                .iter()
+                .filter(|modifier| modifier.stat == stat)
+                .fold((0., 0.), |(flat, percent), modifier| match modifier.kind {
+                    ModifierKind::Flat(x) => (flat + x, percent),
+                    ModifierKind::Percent(x) => (flat, percent + x),
+                });
+            ((base + flat) * (1. + percent)).max(0.)
+        };
+        DerivedStats {
+            max_health: apply(Stat::MaxHealth, 50. + attributes.vitality * 5.),
+            attack: apply(Stat::Attack, attributes.strength / 2.),
+            defense: apply(Stat::Defense, attributes.vitality / 2.),
+            move_speed: apply(Stat::MoveSpeed, 344. + attributes.dexterity * 4.),
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct StatsBundle {
+    attributes: Attributes,
+    modifiers: StatModifiers,
+    derived: DerivedStats,
+}
+
+impl StatsBundle {
+    pub fn new(attributes: Attributes) -> Self {
+        let modifiers = StatModifiers::default();
+        StatsBundle {
+            derived: DerivedStats::compute(&attributes, &modifiers),
+            attributes,
+            modifiers,
+        }
+    }
+
+    pub fn derived(&self) -> DerivedStats {
+        self.derived
+    }
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (expire_timed_modifiers, recompute_stats).chain());
+    }
+}
+
+fn expire_timed_modifiers(
+    time: Res<Time>,
+    mut timed_query: Query<(&mut TimedModifiers, &mut StatModifiers)>,
+) {
+    for (mut timed, mut modifiers) in timed_query.iter_mut() {
+        timed.0.retain_mut(|(source, timer)| {
+            if timer.tick(time.delta()).finished() {
+                modifiers.remove_source(*source);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+type StatsChanged = Or<(Changed<Attributes>, Changed<StatModifiers>)>;
+
+fn recompute_stats(
+    mut stats_query: Query<
+        (
+            &Attributes,
+            &StatModifiers,
+            &mut DerivedStats,
+            Option<&mut Health>,
+        ),
+        StatsChanged,
+    >,
+) {
+    for (attributes, modifiers, mut derived, health) in stats_query.iter_mut() {
+        let computed = DerivedStats::compute(attributes, modifiers);
+        if let Some(mut health) = health {
+            // Keep the amount of missing health the same when the maximum changes
+            let missing = health.max - health.current;
+            health.max = computed.max_health;
+            health.current = (health.max - missing).clamp(0., health.max);
+        }
+        *derived = computed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Attributes, DerivedStats, ModifierKind, ModifierSource, Stat, StatModifier, StatModifiers,
+        StatsBundle,
+    };
+    use crate::combat::Health;
+    use bevy::prelude::*;
+
+    #[test]
+    fn modifiers_apply_flat_before_percent() {
+        let mut modifiers = StatModifiers::default();
+        modifiers.add(StatModifier {
+            source: ModifierSource::Equipment(0),
+            stat: Stat::Attack,
+            kind: ModifierKind::Flat(5.),
+        });
+        modifiers.add(StatModifier {
+            source: ModifierSource::Buff(0),
+            stat: Stat::Attack,
+            kind: ModifierKind::Percent(0.5),
+        });
+        let stats = DerivedStats::compute(&Attributes::default(), &modifiers);
+        assert_eq!(stats.attack, 15.);
+        assert_eq!(
+            stats.move_speed,
+            DerivedStats::compute(&Attributes::default(), &StatModifiers::default()).move_speed
+        );
+
+        modifiers.remove_source(ModifierSource::Buff(0));
+        let stats = DerivedStats::compute(&Attributes::default(), &modifiers);
+        assert_eq!(stats.attack, 10.);
+    }
+
+    #[test]
+    fn changed_attributes_are_recomputed() {
+        let mut app = App::default();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, super::recompute_stats);
+        let entity = app
+            .world_mut()
+            .spawn(StatsBundle::new(Attributes::default()))
+            .insert(Health {
+                current: 90.,
+                max: 100.,
+            })
+            .id();
+        app.update();
+        app.world_mut()
+            .get_mut::<Attributes>(entity)
+            .unwrap()
+            .vitality += 2.;
+        app.update();
+        assert_eq!(
+            app.world().get::<DerivedStats>(entity).unwrap().max_health,
+            110.
+        );
+        assert_eq!(
+            app.world().get::<Health>(entity).unwrap(),
+            &Health {
+                current: 100.,
+                max: 110.
+            }
+        );
+    }
+}
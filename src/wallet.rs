@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Wallet(pub u32);
+
+impl Wallet {
+    pub fn deposit(&mut self, amount: u32) {
+        self.0 = self.0.saturating_add(amount);
+    }
+
+    /// Takes the amount out of the wallet if it holds enough.
+    pub fn withdraw(&mut self, amount: u32) -> bool {
+        match self.0.checked_sub(amount) {
+            Some(remaining) => {
+                self.0 = remaining;
+                true
+            }
+            None => false,
+        }
+    }
+}